@@ -0,0 +1,639 @@
+extern crate proc_macro;
+use self::proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse::Result, parse_macro_input, AngleBracketedGenericArguments, Attribute, Data,
+    Data::Struct, DataEnum, DataStruct, DataUnion, DeriveInput, Error, ExprClosure, Field, Fields,
+    Fields::Named, FieldsNamed, GenericArgument, Ident, Lit, Meta, MetaList, MetaNameValue,
+    NestedMeta, Pat, PatIdent, PatType, PathArguments, PathArguments::AngleBracketed, PathSegment,
+    Type, TypePath,
+};
+
+// Options collected from the `#[builder(...)]` attributes of a single field.
+#[derive(Debug, Default)]
+struct FieldAttribute {
+    // name of the one-element-at-a-time setter, from `each = "..."`
+    each: Option<Ident>,
+    // closure from `setter(transform = "|...| ...")`, its parameters become
+    // the setter's parameters and its result is stored in the field
+    transform: Option<ExprClosure>,
+    // from `default`, an unset field is built as `Default::default()`
+    default: bool,
+}
+
+impl FieldAttribute {
+    // the whole-field setter, renamed to `set_<field>` when `each` takes the field's name
+    fn setter_ident(&self, field_ident: &Ident) -> Ident {
+        match &self.each {
+            Some(each_ident) if each_ident == field_ident => format_ident!("set_{}", field_ident),
+            _ => field_ident.clone(),
+        }
+    }
+
+    fn extend_ident(&self, field_ident: &Ident) -> Ident {
+        format_ident!("{}_extend", field_ident)
+    }
+}
+
+fn parse_setter_attribute(list: &MetaList) -> Result<ExprClosure> {
+    let mut transform = None;
+    for nested in list.nested.iter() {
+        match nested {
+            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Str(closure),
+                ..
+            })) if path.is_ident("transform") => {
+                if transform.is_some() {
+                    return Err(Error::new_spanned(
+                        nested,
+                        "`transform` is specified more than once",
+                    ));
+                }
+                let closure: ExprClosure = closure.parse()?;
+                for input in closure.inputs.iter() {
+                    if !matches!(input, Pat::Type(_)) {
+                        return Err(Error::new_spanned(
+                            input,
+                            "transform closure parameters need a type annotation",
+                        ));
+                    }
+                }
+                transform = Some(closure);
+            }
+            _ => {
+                return Err(Error::new_spanned(
+                    nested,
+                    "expected `transform = \"|...| ...\"`",
+                ))
+            }
+        }
+    }
+    transform.ok_or_else(|| Error::new_spanned(list, "expected `setter(transform = \"...\")`"))
+}
+
+fn parse_field_attribute(f: &Field) -> Result<FieldAttribute> {
+    let mut parsed = FieldAttribute::default();
+    for attr in f.attrs.iter() {
+        if !attr.path.is_ident("builder") {
+            continue;
+        }
+        let meta = attr.parse_meta()?;
+        let unrecognized = || Error::new_spanned(&meta, "expected `builder(each = \"...\")`");
+        let list = match &meta {
+            Meta::List(list) => list,
+            _ => return Err(unrecognized()),
+        };
+        for nested in list.nested.iter() {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(name),
+                    ..
+                })) if path.is_ident("each") => {
+                    if parsed.each.is_some() {
+                        return Err(Error::new_spanned(
+                            nested,
+                            "`each` is specified more than once",
+                        ));
+                    }
+                    parsed.each = Some(name.parse()?);
+                }
+                NestedMeta::Meta(Meta::List(setter)) if setter.path.is_ident("setter") => {
+                    if parsed.transform.is_some() {
+                        return Err(Error::new_spanned(
+                            nested,
+                            "`setter` is specified more than once",
+                        ));
+                    }
+                    parsed.transform = Some(parse_setter_attribute(setter)?);
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("default") => {
+                    if parsed.default {
+                        return Err(Error::new_spanned(
+                            nested,
+                            "`default` is specified more than once",
+                        ));
+                    }
+                    parsed.default = true;
+                }
+                _ => return Err(unrecognized()),
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+// One build method, from `#[builder(build_fn(name = "...", private, error = "..."))]`
+// on the struct. Several `build_fn` entries generate several build methods.
+#[derive(Debug)]
+struct BuildFnAttribute {
+    name: Ident,
+    // emitted as `pub(crate)` instead of `pub`
+    private: bool,
+    // must implement `From<derive_builder::UninitializedFieldError>`
+    error: Option<Type>,
+}
+
+impl Default for BuildFnAttribute {
+    fn default() -> Self {
+        BuildFnAttribute {
+            name: Ident::new("build", Span::call_site()),
+            private: false,
+            error: None,
+        }
+    }
+}
+
+fn parse_build_fn_attribute(list: &MetaList) -> Result<BuildFnAttribute> {
+    let mut parsed = BuildFnAttribute::default();
+    let mut seen = Vec::new();
+    for nested in list.nested.iter() {
+        if let NestedMeta::Meta(meta) = nested {
+            let key = meta.path();
+            if seen.contains(&key) {
+                return Err(Error::new_spanned(
+                    nested,
+                    format!("`{}` is specified more than once", quote!(#key)),
+                ));
+            }
+            seen.push(key);
+        }
+        match nested {
+            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Str(name),
+                ..
+            })) if path.is_ident("name") => {
+                parsed.name = name.parse()?;
+            }
+            NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                path,
+                lit: Lit::Str(error),
+                ..
+            })) if path.is_ident("error") => {
+                parsed.error = Some(error.parse()?);
+            }
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("private") => {
+                parsed.private = true;
+            }
+            _ => {
+                return Err(Error::new_spanned(
+                    nested,
+                    "expected `name = \"...\"`, `private` or `error = \"...\"`",
+                ))
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+fn parse_struct_attribute(attrs: &[Attribute]) -> Result<Vec<BuildFnAttribute>> {
+    let mut build_fns = Vec::<BuildFnAttribute>::new();
+    for attr in attrs.iter() {
+        if !attr.path.is_ident("builder") {
+            continue;
+        }
+        let meta = attr.parse_meta()?;
+        let unrecognized = || Error::new_spanned(&meta, "expected `builder(build_fn(...))`");
+        let list = match &meta {
+            Meta::List(list) => list,
+            _ => return Err(unrecognized()),
+        };
+        for nested in list.nested.iter() {
+            // a bare `build_fn` asks for the default `pub fn build`
+            let parsed = match nested {
+                NestedMeta::Meta(Meta::List(build_fn)) if build_fn.path.is_ident("build_fn") => {
+                    parse_build_fn_attribute(build_fn)?
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("build_fn") => {
+                    BuildFnAttribute::default()
+                }
+                _ => return Err(unrecognized()),
+            };
+            if build_fns.iter().any(|b| b.name == parsed.name) {
+                return Err(Error::new_spanned(
+                    nested,
+                    format!("duplicate build method `{}`", parsed.name),
+                ));
+            }
+            build_fns.push(parsed);
+        }
+    }
+    if build_fns.is_empty() {
+        build_fns.push(BuildFnAttribute::default());
+    }
+    Ok(build_fns)
+}
+
+#[proc_macro_derive(Builder, attributes(builder))]
+pub fn derive(input: TokenStream) -> TokenStream {
+    // eprintln!("TOKENS: {}", input);
+    let ast = parse_macro_input!(input as DeriveInput);
+    // eprintln!("SYN: {:#?}", ast);
+    expand(ast)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+fn expand(ast: DeriveInput) -> Result<TokenStream2> {
+    let struct_ident = ast.ident;
+
+    let fields = match &ast.data {
+        Struct(DataStruct {
+            fields: Named(FieldsNamed { named, .. }),
+            ..
+        }) => named,
+        Struct(DataStruct { fields, .. }) => {
+            let message = "Builder requires a struct with named fields";
+            return Err(match fields {
+                Fields::Unit => Error::new_spanned(&struct_ident, message),
+                _ => Error::new_spanned(fields, message),
+            });
+        }
+        Data::Enum(DataEnum { enum_token, .. }) => {
+            return Err(Error::new_spanned(
+                enum_token,
+                "Builder cannot be derived for enums",
+            ))
+        }
+        Data::Union(DataUnion { union_token, .. }) => {
+            return Err(Error::new_spanned(
+                union_token,
+                "Builder cannot be derived for unions",
+            ))
+        }
+    };
+
+    // eprintln!("fields: {:#?}", fields);
+
+    let field_attrs = fields
+        .iter()
+        .map(parse_field_attribute)
+        .collect::<Result<Vec<_>>>()?;
+    let build_fns = parse_struct_attribute(&ast.attrs)?;
+
+    // used to find out inner type inside Vec
+    let inner_type_fn = |ty: &Type| -> Option<syn::Type> {
+        if let syn::Type::Path(TypePath { path, .. }) = ty {
+            if path.segments.len() == 1 {
+                let seg = path.segments.first().unwrap();
+                if let PathSegment {
+                    ident,
+                    arguments: AngleBracketed(AngleBracketedGenericArguments { args, .. }),
+                } = seg
+                {
+                    if ident == "Vec" && args.len() == 1 {
+                        // to get inner type value
+                        let arg = args.first().unwrap();
+                        // eprintln!("arg: {:#?}", arg);
+                        if let GenericArgument::Type(ty) = arg {
+                            return Some(ty.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        None
+    };
+
+    let struct_init_fields = fields.iter().map(|f| {
+        let ident = &f.ident;
+        if inner_type_fn(&f.ty).is_some() {
+            return quote! { #ident: std::option::Option::Some(std::vec::Vec::new()) };
+        }
+        quote! {
+            #ident: std::option::Option::None
+        }
+    });
+
+    // To handle optional fields
+    let to_actual_type_fn = |f: &Field| -> (syn::Type, bool) {
+        // eprintln!("Field: {:#?}", f);
+        if let Type::Path(TypePath {
+            path: inner_path, ..
+        }) = &f.ty
+        {
+            if inner_path.segments.len() == 1 {
+                let seg = &inner_path.segments.first().unwrap();
+                if seg.ident == "Option" {
+                    if let PathSegment {
+                        arguments:
+                            PathArguments::AngleBracketed(AngleBracketedGenericArguments {
+                                args: inner_args,
+                                ..
+                            }),
+                        ..
+                    } = seg
+                    {
+                        if inner_args.len() == 1 {
+                            if let GenericArgument::Type(ty) = &inner_args.first().unwrap() {
+                                return (ty.clone(), true);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        (f.ty.clone(), false)
+    };
+
+    // Generated:
+    // #[derive(Builder)]
+    // pub struct Command {
+    //     executable: String,
+    //     #[builder(each = "arg")]
+    //     args: Vec<String>,
+    //     #[builder(each = "env")]
+    //     env: Vec<String>,
+    //     current_dir: Option<String>,
+    // }
+
+    let builder_struct_fields = fields.iter().map(|f| {
+        let ident = &f.ident;
+        let (actual_ty, _) = to_actual_type_fn(f);
+
+        quote! {
+            #ident : std::option::Option<#actual_ty>
+        }
+    });
+    // eprintln!("option fields: {:#?}", option_fields);
+
+    // Generated:
+    // pub fn executable(&mut self, executable: String) -> &mut Self {
+    //     self.executable = Some(executable);
+    //     self
+    // }
+    // pub fn current_dir(&mut self, current_dir: String) -> &mut Self {
+    //     self.current_dir = Some(current_dir);
+    //     self
+    // }
+    //
+    // and for `#[builder(each = "arg")] args: Vec<String>`:
+    //
+    // pub fn args(&mut self, args: Vec<String>) -> &mut Self { ... }
+    // pub fn arg(&mut self, arg: impl Into<String>) -> &mut Self { ... }
+    // pub fn args_extend(&mut self, iter: impl IntoIterator<Item = impl Into<String>>) -> &mut Self { ... }
+    //
+    // If `each` has the same name as the field, the whole-Vec setter is kept
+    // as `set_<field>` so the list can still be assigned all at once.
+    //
+    // and for `#[builder(setter(transform = "|secs: u64| Duration::from_secs(secs)"))]`:
+    //
+    // pub fn timeout(&mut self, secs: u64) -> &mut Self {
+    //     self.timeout = Some({
+    //         let transform = |secs: u64| Duration::from_secs(secs);
+    //         transform(secs)
+    //     });
+    //     self
+    // }
+
+    // Reject `each` on fields that are not a Vec, and any two generated
+    // methods that would end up with the same name.
+    let mut method_idents = Vec::<Ident>::new();
+    let mut add_method = |ident: Ident| {
+        if method_idents.contains(&ident) {
+            return Err(Error::new(
+                ident.span(),
+                format!("duplicate builder method `{}`", ident),
+            ));
+        }
+        method_idents.push(ident);
+        Ok(())
+    };
+    for (f, attrs) in fields.iter().zip(field_attrs.iter()) {
+        let field_ident = f.ident.as_ref().unwrap();
+        add_method(attrs.setter_ident(field_ident))?;
+        if let Some(each_ident) = &attrs.each {
+            let (ty, _) = to_actual_type_fn(f);
+            if inner_type_fn(&ty).is_none() {
+                return Err(Error::new(
+                    each_ident.span(),
+                    "`each` can only be used on a field of type Vec<T>",
+                ));
+            }
+            add_method(each_ident.clone())?;
+            add_method(attrs.extend_ident(field_ident))?;
+        }
+    }
+    for build_fn in build_fns.iter() {
+        add_method(build_fn.name.clone())?;
+    }
+
+    // Parameters of the whole-field setter and the expression computing the
+    // value it stores, either the field type itself or the transform closure.
+    let setter_parts_fn = |f: &Field, attrs: &FieldAttribute| -> (TokenStream2, TokenStream2) {
+        let field_ident = f.ident.as_ref().unwrap();
+        let (ty, _) = to_actual_type_fn(f);
+        match &attrs.transform {
+            Some(closure) => {
+                // the closure parameters are all `Pat::Type`, checked while parsing
+                let params = closure
+                    .inputs
+                    .iter()
+                    .enumerate()
+                    .map(|(i, input)| match input {
+                        Pat::Type(PatType { pat, ty, .. }) => match &**pat {
+                            Pat::Ident(PatIdent {
+                                ident,
+                                subpat: None,
+                                ..
+                            }) => (ident.clone(), ty),
+                            _ => (format_ident!("arg{}", i), ty),
+                        },
+                        _ => unreachable!(),
+                    });
+                let (param_idents, param_types): (Vec<_>, Vec<_>) = params.unzip();
+                (
+                    quote!(#( #param_idents: #param_types ),*),
+                    quote!({
+                        let transform = #closure;
+                        transform(#( #param_idents ),*)
+                    }),
+                )
+            }
+            None => (quote!(#field_ident: #ty), quote!(#field_ident)),
+        }
+    };
+
+    let builder_methods = fields.iter().zip(field_attrs.iter()).map(|(f, attrs)| {
+        let field_ident = f.ident.as_ref().unwrap();
+        let (ty, _) = to_actual_type_fn(f);
+
+        let setter_ident = attrs.setter_ident(field_ident);
+        let (setter_params, setter_value) = setter_parts_fn(f, attrs);
+        let setter = quote! {
+            pub fn #setter_ident(&mut self, #setter_params) -> &mut Self {
+                self.#field_ident = std::option::Option::Some(#setter_value);
+                self
+            }
+        };
+
+        let each_ident = match &attrs.each {
+            Some(each_ident) => each_ident,
+            None => return setter,
+        };
+
+        let extend_ident = attrs.extend_ident(field_ident);
+        // if the type is wrapped in Vec, we need to extract inner 'true' type
+        let inner_type = inner_type_fn(&ty);
+
+        quote! {
+            #setter
+
+            pub fn #each_ident(&mut self, #each_ident: impl std::convert::Into<#inner_type>) -> &mut Self {
+                self.#field_ident
+                    .get_or_insert_with(std::vec::Vec::new)
+                    .push(std::convert::Into::into(#each_ident));
+                self
+            }
+
+            pub fn #extend_ident(
+                &mut self,
+                iter: impl std::iter::IntoIterator<Item = impl std::convert::Into<#inner_type>>,
+            ) -> &mut Self {
+                self.#field_ident
+                    .get_or_insert_with(std::vec::Vec::new)
+                    .extend(std::iter::IntoIterator::into_iter(iter).map(std::convert::Into::into));
+                self
+            }
+        }
+    });
+
+    // Generated
+    // pub fn build(&mut self) -> Result<Command, Box<dyn std::error::Error>> {
+    //     Ok(Command {
+    //         executable: match self.executable {
+    //             Some(ref value) => value.clone(),
+    //             None => return Err(From::from(UninitializedFieldError::new("executable"))),
+    //         },
+    //         current_dir: self.current_dir.clone(),
+    //     })
+    // }
+    //
+    // `#[builder(build_fn(name = "finish", private, error = "MyError"))]` turns
+    // this into `pub(crate) fn finish(&mut self) -> Result<Command, MyError>`.
+
+    let builder_build_fields = fields
+        .iter()
+        .zip(field_attrs.iter())
+        .map(|(f, attrs)| {
+            let ident = &f.ident;
+            let (_, is_optional) = to_actual_type_fn(f);
+            if is_optional {
+                quote! {
+                    #ident: std::clone::Clone::clone(&self.#ident)
+                }
+            } else if attrs.default {
+                quote! {
+                    #ident: match self.#ident {
+                        std::option::Option::Some(ref value) => std::clone::Clone::clone(value),
+                        std::option::Option::None => std::default::Default::default(),
+                    }
+                }
+            } else {
+                quote! {
+                    #ident: match self.#ident {
+                        std::option::Option::Some(ref value) => std::clone::Clone::clone(value),
+                        std::option::Option::None => {
+                            return std::result::Result::Err(std::convert::From::from(
+                                derive_builder::UninitializedFieldError::new(stringify!(#ident)),
+                            ))
+                        }
+                    }
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let build_methods = build_fns.iter().map(|build_fn| {
+        let name = &build_fn.name;
+        let vis = if build_fn.private {
+            quote!(pub(crate))
+        } else {
+            quote!(pub)
+        };
+        let error = match &build_fn.error {
+            Some(error) => quote!(#error),
+            None => quote!(std::boxed::Box<dyn std::error::Error>),
+        };
+        quote! {
+            #vis fn #name(&mut self) -> std::result::Result<#struct_ident, #error> {
+                std::result::Result::Ok(#struct_ident {
+                    #( #builder_build_fields ,)*
+                })
+            }
+        }
+    });
+
+    // Generated, only when no field needs to be set before building, i.e. all
+    // of them are Option, Vec or `#[builder(default)]`:
+    // impl Command {
+    //     pub fn with_current_dir(mut self, current_dir: String) -> Self {
+    //         self.current_dir = Some(current_dir);
+    //         self
+    //     }
+    //     pub fn with_args(mut self, args: Vec<String>) -> Self {
+    //         self.args = args;
+    //         self
+    //     }
+    // }
+
+    let all_fields_optional = fields.iter().zip(field_attrs.iter()).all(|(f, attrs)| {
+        let (ty, is_optional) = to_actual_type_fn(f);
+        is_optional || attrs.default || inner_type_fn(&ty).is_some()
+    });
+    let with_methods = fields.iter().zip(field_attrs.iter()).map(|(f, attrs)| {
+        let field_ident = f.ident.as_ref().unwrap();
+        let (_, is_optional) = to_actual_type_fn(f);
+        let with_ident = format_ident!("with_{}", field_ident);
+        let (setter_params, setter_value) = setter_parts_fn(f, attrs);
+        let value = if is_optional {
+            quote!(std::option::Option::Some(#setter_value))
+        } else {
+            setter_value
+        };
+        quote! {
+            pub fn #with_ident(mut self, #setter_params) -> Self {
+                self.#field_ident = #value;
+                self
+            }
+        }
+    });
+    let with_impl = if all_fields_optional {
+        quote! {
+            impl #struct_ident {
+                #( #with_methods )*
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    let builder_ident = format_ident!("{}Builder", struct_ident);
+    let expanded = quote! {
+        impl #struct_ident {
+            pub fn builder() -> #builder_ident {
+                #builder_ident {
+                    #( #struct_init_fields ,)*
+                }
+            }
+        }
+
+        pub struct #builder_ident {
+            #( #builder_struct_fields ,)*
+        }
+
+        impl #builder_ident {
+            #( #builder_methods )*
+
+            #( #build_methods )*
+        }
+
+        #with_impl
+
+    };
+    Ok(expanded)
+}
@@ -1,283 +1,38 @@
-extern crate proc_macro;
-use proc_macro2::{Ident, Span};
-use self::proc_macro::TokenStream;
-use quote::{format_ident, quote};
-use syn::{
-    parse_macro_input, AngleBracketedGenericArguments, Data::Struct, DataStruct, DeriveInput,
-    Field, Fields::Named, FieldsNamed, GenericArgument, PathArguments, PathArguments:: { AngleBracketed }, PathSegment, Type, TypePath,
-    LitStr, Token, parse::{ Parse, ParseStream, Result}
-};
-
-#[derive(Debug)]
-struct BuilderAttribute {
-    ident: Ident,
-    name: LitStr,
+// Crates that have the "proc-macro" crate type are only allowed to export
+// procedural macros. The code generated by #[derive(Builder)] needs an error
+// type that user code can name (for example to write
+// `impl From<UninitializedFieldError> for MyError`), so the derive lives in the
+// derive_builder_impl crate and is re-exported from here together with the
+// supporting types.
+pub use derive_builder_impl::Builder;
+
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// Returned by a generated build method when a required field was never set.
+///
+/// Builders configured with `#[builder(build_fn(error = "MyError"))]` convert
+/// this into `MyError` through `From<UninitializedFieldError>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UninitializedFieldError {
+    field_name: &'static str,
 }
 
-impl Parse for BuilderAttribute {
-    fn parse(input: ParseStream) -> Result<Self> {
-        let ident: Ident = input.parse()?;
-        input.parse::<Token![=]>()?;
-        let name: LitStr = input.parse()?;
-        if name.value() != "each" {
-            syn::Error::new(input.span(), "expected `builder(each = \"...\")`").to_compile_error();
-        }
-        Ok(BuilderAttribute {
-            ident,
-            name,
-        })
+impl UninitializedFieldError {
+    pub fn new(field_name: &'static str) -> Self {
+        UninitializedFieldError { field_name }
     }
-}
-
-#[proc_macro_derive(Builder, attributes(builder))]
-pub fn derive(input: TokenStream) -> TokenStream {
-    // eprintln!("TOKENS: {}", input);
-    let ast = parse_macro_input!(input as DeriveInput);
-    // eprintln!("SYN: {:#?}", ast);
-    // unimplemented!()
-    let struct_ident = ast.ident;
-
-    let fields = if let Struct(DataStruct {
-        fields: Named(FieldsNamed { ref named, .. }),
-        ..
-    }) = ast.data
-    {
-        named
-    } else {
-        unimplemented!();
-    };
-
-    // eprintln!("fields: {:#?}", fields);
-
-    let struct_init_fields = fields.iter().map(|f| {
-        let ident = &f.ident;
-        if let Type::Path(TypePath{ path ,.. } ) = &f.ty {
-            // eprintln!("Path: {:#?}", path);
-            if path.segments.len() == 1 {
-                let p =  path.segments.first().unwrap();
-                if p.ident == "Vec" {
-                    return quote! { #ident: std::option::Option::<Vec<String>>::Some(Vec::<String>::new()) };
-                }
-            }
-        }
-        quote! {
-            #ident: std::option::Option::<String>::None
-        }
-    });
-
-    // To handle optional fields
-    let to_actual_type_fn = |f: &Field| -> (syn::Type, bool) {
-        // eprintln!("Field: {:#?}", f);
-        if let Type::Path(TypePath {
-            path: inner_path, ..
-        }) = &f.ty
-        {
-            if inner_path.segments.len() == 1 {
-                let seg = &inner_path.segments.first().unwrap();
-                if seg.ident.to_string() == "Option" {
-                    if let PathSegment {
-                        arguments:
-                            PathArguments::AngleBracketed(AngleBracketedGenericArguments {
-                                args: inner_args,
-                                ..
-                            }),
-                        ..
-                    } = seg
-                    {
-                        if inner_args.len() == 1 {
-                            if let GenericArgument::Type(ty) = &inner_args.first().unwrap() {
-                                return (ty.clone(), true);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        return (f.ty.clone(), false);
-    };
-
-    // used to find out inner type inside Vec
-    let inner_type_fn = |ty: &Type| -> Option<syn::Type> {
-        if let syn::Type::Path(TypePath {
-            path, ..
-        }) = ty {
-            if path.segments.len() == 1 {
-                let seg = path.segments.first().unwrap();
-                if let PathSegment {
-                    ident,
-                    arguments: AngleBracketed(
-                        AngleBracketedGenericArguments {
-                            args, ..
-                        }
-                    )
-                } = seg {
-                    if ident == "Vec" {
-                        if args.len() == 1 {
-                            // to get inner type value
-                            let arg = args.first().unwrap();
-                            // eprintln!("arg: {:#?}", arg);
-                            if let GenericArgument::Type(ty) = arg {
-                                return Some(ty.clone());
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        else {
-        }
-
-        None
-    };
-
-    // Generated:
-    // #[derive(Builder)]
-    // pub struct Command {
-    //     executable: String,
-    //     #[builder(each = "arg")]
-    //     args: Vec<String>,
-    //     #[builder(each = "env")]
-    //     env: Vec<String>,
-    //     current_dir: Option<String>,
-    // }
-
-    let builder_struct_fields = fields.iter().map(|f| {
-        let ident = &f.ident;
-        let (actual_ty, _) = to_actual_type_fn(&f);
-
-        quote! {
-            #ident : std::option::Option<#actual_ty>
-        }
-    });
-    // eprintln!("option fields: {:#?}", option_fields);
-
-    // Generated:
-    // pub fn executable(&mut self, executable: String) -> &mut Self {
-    //     self.executable = Some(executable);
-    //     self
-    // }
-    // pub fn args(&mut self, args: Vec<String>) -> &mut Self {
-    //     self.args = Some(args);
-    //     self
-    // }
-    // pub fn env(&mut self, env: Vec<String>) -> &mut Self {
-    //     self.env = Some(env);
-    //     self
-    // }
-    // pub fn current_dir(&mut self, current_dir: String) -> &mut Self {
-    //     self.current_dir = Some(current_dir);
-    //     self
-    // }
 
-    let builder_methods = fields.iter().map(|f| {
-        let field_ident = &f.ident;
-        let (ty, _) = to_actual_type_fn(&f);
-        
-        // eprintln!("actual type: {:#?}", ty);
-        let mut tokenstream = 
-            quote! {
-                pub fn #field_ident(&mut self, #field_ident: #ty) -> &mut Self {
-                    self.#field_ident = Some(#field_ident);
-                    self
-                }
-            };
-        
-        for attr in f.attrs.iter() {
-            let segs = &attr.path.segments;
-            if segs.len() == 1 {
-                let seg = segs.first().unwrap();
-                let attr_ident = &seg.ident;
-                if attr_ident == "builder"  {
-                    // let meta = attr.tokens.parse_meta().expect("only attribute name 'each' is supported");
-                    let parsed = attr.parse_args::<BuilderAttribute>().expect("not parsed");
-                    // eprintln!("parsed meta: {:#?}", parsed);
-
-                    if parsed.ident == "each" {
-                        let name = parsed.name.value();
-                        let arg_ident = Ident::new(&name, Span::call_site());
-                        // if the type is wrapped in Vec, we need to extract inner 'true' type
-                        let inner_type = inner_type_fn(&ty);
-                        let ts = quote! {
-                            fn #arg_ident(&mut self, #arg_ident: #inner_type) -> &mut Self {
-                                if self.#field_ident.is_none() {
-                                    // this is hard coded type
-                                    self.#field_ident = Some(Vec::<#inner_type>::new());
-                                }
-                                if let Some(a) = &mut self.#field_ident {
-                                    a.push(#arg_ident);
-                                }
-                                self
-                            }
-                        };
-                        if let Some(ident) = field_ident {
-                            let field_name = ident.to_string();
-                            if name == field_name {
-                                tokenstream = ts;
-                            }
-                            else {
-                                tokenstream.extend(ts);
-                            }
-                        }
-                        // eprintln!("arg name: {:#?}", name);
-                    }
-                }
-            }
-            // eprintln!("field {} attr: {:#?}", ident.as_ref().unwrap().to_string(), attr);
-            
-        }
-
-        tokenstream        
-    });
-
-
-    // Generated
-    // pub fn build(&mut self) -> Result<Command, Box<dyn std::error::Error>> {
-    //     Ok(Command {
-    //         executable: self.executable.clone().expect("executable is not set"),
-    //         args: self.args.clone().expect("args is not set"),
-    //         env: self.env.clone().expect("env is not set"),
-    //         current_dir: self.current_dir.clone(),
-    //     })
-    // }
-
-    let builder_build_fields = fields.iter().map(|f| {
-        let ident = &f.ident;
-        let (_, is_optional) = to_actual_type_fn(&f);
-        if is_optional {
-            quote! {
-                #ident: self.#ident.clone()
-            }
-        } else {
-            quote! {
-                #ident: self.#ident.clone().expect(concat!(stringify!(#ident), " is not set"))
-            }
-        }
-    });
-
-    let builder_ident = format_ident!("{}Builder", struct_ident);
-    let expanded = quote! {
-        impl #struct_ident {
-            pub fn builder() -> #builder_ident {
-                #builder_ident {
-                    #( #struct_init_fields ,)*
-                }
-            }
-        }
-
-        pub struct #builder_ident {
-            #( #builder_struct_fields ,)*
-        }
-
-        impl #builder_ident {
-            #( #builder_methods )*
-
-            pub fn build(&mut self) -> std::result::Result<#struct_ident, std::boxed::Box<dyn std::error::Error>> {
-                std::result::Result::<#struct_ident, std::boxed::Box<dyn std::error::Error>>::Ok(#struct_ident {
-                    #( #builder_build_fields ,)*
-                })
-            }
-        }
+    /// Name of the field that was not initialized.
+    pub fn field_name(&self) -> &'static str {
+        self.field_name
+    }
+}
 
-    };
-    TokenStream::from(expanded)
+impl Display for UninitializedFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}` must be initialized", self.field_name)
+    }
 }
+
+impl Error for UninitializedFieldError {}
@@ -0,0 +1,46 @@
+// Besides the one-element-at-a-time method generated for
+// #[builder(each = "...")], generate a `<field>_extend` method which appends
+// every item of an iterator, converting each item with Into. The single-element
+// method also accepts anything convertible into the element type.
+//
+// When the `each` name is the same as the field name, the all-at-once setter
+// is still generated, renamed to `set_<field>`, so that the whole Vec can be
+// replaced in one call.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env")]
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build")
+        .args_extend(vec!["--release", "--locked"])
+        .arg("--verbose".to_owned())
+        .env("RUST_LOG=debug")
+        .build()
+        .unwrap();
+
+    assert_eq!(command.args, ["build", "--release", "--locked", "--verbose"]);
+    assert_eq!(command.env, ["RUST_LOG=debug"]);
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .args(vec!["test".to_owned()])
+        .env("IGNORED=1")
+        .set_env(vec!["CARGO_INCREMENTAL=0".to_owned()])
+        .env_extend(Some("RUSTFLAGS=-Dwarnings"))
+        .build()
+        .unwrap();
+
+    assert_eq!(command.args, ["test"]);
+    assert_eq!(command.env, ["CARGO_INCREMENTAL=0", "RUSTFLAGS=-Dwarnings"]);
+}
@@ -0,0 +1,49 @@
+// The generated build method can be configured through a struct-level
+// #[builder(build_fn(...))] attribute:
+//
+//   - `name = "..."` renames the method,
+//   - `private` makes it visible only inside the crate,
+//   - `error = "..."` replaces Box<dyn Error> with a caller-provided error type,
+//     which must implement From<derive_builder::UninitializedFieldError>.
+//
+// Giving several build_fn entries generates one build method for each of them.
+
+use derive_builder::{Builder, UninitializedFieldError};
+
+#[derive(Debug, PartialEq)]
+pub enum MyError {
+    Missing(&'static str),
+}
+
+impl From<UninitializedFieldError> for MyError {
+    fn from(err: UninitializedFieldError) -> Self {
+        MyError::Missing(err.field_name())
+    }
+}
+
+#[derive(Builder)]
+#[builder(build_fn(name = "finish", private, error = "MyError"))]
+#[builder(build_fn)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build")
+        .finish()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, ["build"]);
+    assert!(command.current_dir.is_none());
+
+    let err = Command::builder().arg("build").finish().err().unwrap();
+    assert_eq!(err, MyError::Missing("executable"));
+
+    let err = Command::builder().build().err().unwrap();
+    assert_eq!(err.to_string(), "`executable` must be initialized");
+}
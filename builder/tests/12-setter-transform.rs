@@ -0,0 +1,35 @@
+// A field can ask for its setter to take different arguments than the field
+// type with #[builder(setter(transform = "|...| ..."))]. The closure's typed
+// parameters become the setter's parameters and the value it returns is stored
+// in the field. For Option fields the closure returns the inner type.
+
+use derive_builder::Builder;
+use std::time::Duration;
+
+#[derive(Builder)]
+pub struct Request {
+    #[builder(setter(transform = "|url: &str| url.to_owned()"))]
+    url: String,
+    #[builder(setter(transform = "|secs: u64| Duration::from_secs(secs)"))]
+    timeout: Duration,
+    #[builder(setter(transform = "|name: &str, value: &str| format!(\"{}: {}\", name, value)"))]
+    header: Option<String>,
+    #[builder(each = "arg", setter(transform = "|args: &[&str]| args.iter().map(|a| a.to_string()).collect()"))]
+    args: Vec<String>,
+}
+
+fn main() {
+    let request = Request::builder()
+        .url("https://example.com")
+        .timeout(30)
+        .header("Accept", "*/*")
+        .args(&["-v", "-L"])
+        .arg("-s")
+        .build()
+        .unwrap();
+
+    assert_eq!(request.url, "https://example.com");
+    assert_eq!(request.timeout, Duration::from_secs(30));
+    assert_eq!(request.header.as_deref(), Some("Accept: */*"));
+    assert_eq!(request.args, ["-v", "-L", "-s"]);
+}
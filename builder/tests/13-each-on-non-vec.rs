@@ -0,0 +1,14 @@
+// The one-element-at-a-time setter generated for #[builder(each = "...")]
+// pushes onto a Vec, so the attribute only makes sense on Vec fields. Point the
+// error at the name given to `each`.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: String,
+}
+
+fn main() {}
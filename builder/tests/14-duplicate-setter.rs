@@ -0,0 +1,16 @@
+// Every field, `each` and `build_fn` attribute contributes methods to the
+// builder. Two of them ending up with the same name must be reported at the
+// second one rather than as a "duplicate definitions" error inside the
+// expanded code.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    arg: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+// Builder is only meaningful for structs with named fields. Deriving it on a
+// union is an error pointing at the `union` keyword.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub union Value {
+    int: u64,
+    float: f64,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+// The setters of the generated builder are named after the fields, so tuple
+// structs are rejected with an error pointing at their fields.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command(String, Vec<String>);
+
+fn main() {}
@@ -0,0 +1,14 @@
+// An option given twice for the same field or build method leaves it unclear
+// which one is meant. Report the repeated option instead of silently keeping
+// the last one.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg", each = "argument")]
+    args: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,40 @@
+// When none of the fields has to be set before building -- every field is an
+// Option, a Vec, or marked #[builder(default)] -- the struct also gets fluent
+// `with_<field>` methods that take and return the struct itself, so no
+// builder()...build().unwrap() round trip is needed. They accept the same
+// arguments as the corresponding builder setter.
+//
+// Structs with a required field, like Command in the earlier tests, don't get
+// these methods since there would be no value to start from.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Default)]
+pub struct Options {
+    #[builder(each = "flag")]
+    flags: Vec<String>,
+    #[builder(default)]
+    jobs: usize,
+    #[builder(default, setter(transform = "|path: &str| path.to_owned()"))]
+    target_dir: String,
+    color: Option<bool>,
+}
+
+fn main() {
+    let options = Options::default()
+        .with_flags(vec!["--locked".to_owned()])
+        .with_jobs(4)
+        .with_target_dir("target")
+        .with_color(true);
+
+    assert_eq!(options.flags, ["--locked"]);
+    assert_eq!(options.jobs, 4);
+    assert_eq!(options.target_dir, "target");
+    assert_eq!(options.color, Some(true));
+
+    let options = Options::builder().flag("--offline").build().unwrap();
+    assert_eq!(options.flags, ["--offline"]);
+    assert_eq!(options.jobs, 0);
+    assert_eq!(options.target_dir, "");
+    assert_eq!(options.color, None);
+}
@@ -10,4 +10,13 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-each-extend.rs");
+    t.pass("tests/11-build-fn.rs");
+    t.pass("tests/12-setter-transform.rs");
+    t.compile_fail("tests/13-each-on-non-vec.rs");
+    t.compile_fail("tests/14-duplicate-setter.rs");
+    t.compile_fail("tests/15-union.rs");
+    t.compile_fail("tests/16-tuple-struct.rs");
+    t.compile_fail("tests/17-conflicting-options.rs");
+    t.pass("tests/18-with-methods.rs");
 }
@@ -0,0 +1,663 @@
+use crate::format::{self, Argument};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
+use syn::{
+    parse::Result, punctuated::Punctuated, Attribute, Error, Field, Lit, LitInt, LitStr, Meta,
+    MetaNameValue, NestedMeta, Path, Token, Type, WherePredicate,
+};
+
+// Every option of `#[debug(...)]` with how it is written, for diagnostics.
+const OPTIONS: &[(&str, &str)] = &[
+    ("bound", "bound = \"...\""),
+    ("rename", "rename = \"...\""),
+    ("non_exhaustive", "non_exhaustive"),
+    ("compact", "compact"),
+    ("with", "with = \"...\""),
+    ("transparent", "transparent"),
+    ("max_depth", "max_depth = N"),
+    ("skip", "skip"),
+    (
+        "redact",
+        "redact` or `redact = \"len\"` or `redact = \"hash\"",
+    ),
+    ("truncate", "truncate = N"),
+    ("sorted", "sorted"),
+    ("pretty_field", "pretty_field = \"...\""),
+    ("hex", "hex"),
+    ("bin", "bin"),
+    ("bytes", "bytes"),
+    ("duration", "duration"),
+    ("via", "via = \"Type\""),
+];
+
+const CONTAINER_OPTIONS: &[&str] = &[
+    "bound",
+    "rename",
+    "non_exhaustive",
+    "compact",
+    "with",
+    "transparent",
+    "max_depth",
+];
+
+const VARIANT_OPTIONS: &[&str] = &["rename", "transparent"];
+
+const FIELD_OPTIONS: &[&str] = &[
+    "skip",
+    "redact",
+    "with",
+    "truncate",
+    "sorted",
+    "rename",
+    "bound",
+    "pretty_field",
+    "hex",
+    "bin",
+    "bytes",
+    "duration",
+    "via",
+];
+
+// The error for an item inside `#[debug(...)]` which none of the accepted
+// options matched, pointing at the part that is wrong: the value of a known
+// option written the wrong way, or the name of an option that doesn't belong
+// on this `place` or doesn't exist.
+fn unexpected_option(nested: &NestedMeta, accepted: &[&str], place: &str) -> Error {
+    let path = match nested {
+        NestedMeta::Meta(meta) => meta.path(),
+        NestedMeta::Lit(lit) => {
+            return Error::new_spanned(lit, "expected an option name, not a literal");
+        }
+    };
+    let name = match path.get_ident() {
+        Some(ident) => ident.to_string(),
+        None => return Error::new_spanned(path, "expected an option name, not a path"),
+    };
+    if accepted.contains(&name.as_str()) {
+        let usage = OPTIONS
+            .iter()
+            .find(|(option, _)| *option == name)
+            .unwrap()
+            .1;
+        let message = format!("expected `{}`", usage);
+        return match nested {
+            NestedMeta::Meta(Meta::NameValue(name_value)) => {
+                Error::new_spanned(&name_value.lit, message)
+            }
+            _ => Error::new_spanned(nested, message),
+        };
+    }
+    if OPTIONS.iter().any(|(option, _)| *option == name) {
+        let places: Vec<&str> = [
+            (CONTAINER_OPTIONS, "structs and enums"),
+            (VARIANT_OPTIONS, "enum variants"),
+            (FIELD_OPTIONS, "fields"),
+        ]
+        .iter()
+        .filter(|(options, _)| options.contains(&name.as_str()))
+        .map(|(_, places)| *places)
+        .collect();
+        return Error::new_spanned(
+            path,
+            format!(
+                "`{}` is not accepted on {}, only on {}",
+                name,
+                place,
+                places.join(" or "),
+            ),
+        );
+    }
+    let expected: Vec<String> = accepted
+        .iter()
+        .map(|option| format!("`{}`", option))
+        .collect();
+    Error::new_spanned(
+        path,
+        format!(
+            "unknown option `{}`, expected one of {}",
+            name,
+            expected.join(", "),
+        ),
+    )
+}
+
+// The error for a `#[debug]` attribute that is neither `#[debug = "..."]` nor
+// `#[debug(...)]`.
+fn unexpected_meta(meta: &Meta) -> Error {
+    match meta {
+        Meta::NameValue(name_value) => {
+            Error::new_spanned(&name_value.lit, "expected a format string")
+        }
+        _ => Error::new_spanned(meta, "expected `debug = \"...\"` or `debug(...)`"),
+    }
+}
+
+// Options may be spread over several `#[debug]` attributes, but each one can
+// only be given once per item. `seen` keeps the options given so far together
+// with where, for `check_field_conflicts`.
+fn check_duplicate(
+    seen: &mut Vec<(String, TokenStream2)>,
+    name: String,
+    tokens: &dyn ToTokens,
+) -> Result<()> {
+    if seen.iter().any(|(seen, _)| *seen == name) {
+        return Err(Error::new_spanned(
+            tokens,
+            format!("`{}` is specified more than once", name),
+        ));
+    }
+    seen.push((name, tokens.to_token_stream()));
+    Ok(())
+}
+
+// Field options that each decide by themselves what gets printed for the
+// field, so at most one of them can be used.
+const PRINTING_OPTIONS: &[&str] = &[
+    "redact", "with", "truncate", "sorted", "hex", "bin", "bytes", "duration", "via",
+];
+
+// Field options giving a format string, which only `via` leaves in effect.
+const FORMAT_OPTIONS: &[&str] = &["debug = \"...\"", "pretty_field"];
+
+// Rejects field options that would silently override one another, pointing at
+// the later of the two: anything next to `skip`, two options from
+// `PRINTING_OPTIONS`, or a format string next to one of them other than `via`.
+fn check_field_conflicts(seen: &[(String, TokenStream2)]) -> Result<()> {
+    let conflict = |a: &str, b: &str| {
+        let printing = |name: &str| PRINTING_OPTIONS.contains(&name);
+        let format = |name: &str| FORMAT_OPTIONS.contains(&name);
+        a == "skip"
+            || b == "skip"
+            || (printing(a) && printing(b))
+            || (format(a) && printing(b) && b != "via")
+            || (printing(a) && a != "via" && format(b))
+    };
+    for (i, (later, tokens)) in seen.iter().enumerate() {
+        if let Some((earlier, _)) = seen[..i]
+            .iter()
+            .find(|(earlier, _)| conflict(earlier, later))
+        {
+            return Err(Error::new_spanned(
+                tokens,
+                format!("`{}` cannot be combined with `{}`", later, earlier),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// The name a nested option is tracked under by `check_duplicate`.
+fn option_name(nested: &NestedMeta) -> Option<String> {
+    match nested {
+        NestedMeta::Meta(meta) => meta.path().get_ident().map(ToString::to_string),
+        NestedMeta::Lit(_) => None,
+    }
+}
+
+// Options collected from `#[debug(...)]` attributes on the struct or enum.
+#[derive(Default)]
+pub struct ContainerAttribute {
+    // from `bound = "..."`, replaces every inferred bound when present
+    pub bound: Option<Punctuated<WherePredicate, Token![,]>>,
+    // from `rename = "..."`, the struct name shown in the output; rejected on
+    // enums, which print the names of their variants
+    pub rename: Option<LitStr>,
+    // from `non_exhaustive`, output ends with `..` after the shown fields
+    pub non_exhaustive: bool,
+    // from `compact`, prints on one line even under `{:#?}`
+    pub compact: bool,
+    // from `with = "path::to::fn"`, only for unions, a
+    // `fn(&Self, &mut fmt::Formatter) -> fmt::Result` printing the union
+    pub with: Option<Path>,
+    // from `#[debug = "..."]` on a struct, a format string referring to the
+    // fields by name, or by index for tuple structs, e.g. "user:{0}"
+    pub format: Option<LitStr>,
+    // from `transparent`, a struct with a single field prints as that field
+    pub transparent: bool,
+    // from `max_depth = N`, how many values with this option may be nested
+    // while printing before the rest is elided as `...`; a value nested in
+    // itself prints as `<cycle>`
+    pub max_depth: Option<LitInt>,
+}
+
+pub fn parse_container_attribute(attrs: &[Attribute]) -> Result<ContainerAttribute> {
+    let mut parsed = ContainerAttribute::default();
+    let mut seen = Vec::new();
+    for attr in attrs.iter() {
+        if !attr.path.is_ident("debug") {
+            continue;
+        }
+        let meta = attr.parse_meta()?;
+        let list = match &meta {
+            Meta::NameValue(MetaNameValue {
+                lit: Lit::Str(format),
+                ..
+            }) => {
+                check_duplicate(&mut seen, "debug = \"...\"".to_owned(), &meta)?;
+                format::parse(format)?;
+                parsed.format = Some(format.clone());
+                continue;
+            }
+            Meta::List(list) => list,
+            _ => return Err(unexpected_meta(&meta)),
+        };
+        for nested in list.nested.iter() {
+            if let Some(name) = option_name(nested) {
+                check_duplicate(&mut seen, name, nested)?;
+            }
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(bound),
+                    ..
+                })) if path.is_ident("bound") => {
+                    parsed.bound = Some(bound.parse_with(Punctuated::parse_terminated)?);
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(rename),
+                    ..
+                })) if path.is_ident("rename") => {
+                    parsed.rename = Some(rename.clone());
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("non_exhaustive") => {
+                    parsed.non_exhaustive = true;
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("compact") => {
+                    parsed.compact = true;
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("transparent") => {
+                    parsed.transparent = true;
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Int(max_depth),
+                    ..
+                })) if path.is_ident("max_depth") => {
+                    if max_depth.base10_parse::<usize>()? == 0 {
+                        return Err(Error::new_spanned(
+                            max_depth,
+                            "`max_depth` has to be at least 1",
+                        ));
+                    }
+                    parsed.max_depth = Some(max_depth.clone());
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(with),
+                    ..
+                })) if path.is_ident("with") => {
+                    parsed.with = Some(with.parse()?);
+                }
+                _ => {
+                    return Err(unexpected_option(
+                        nested,
+                        CONTAINER_OPTIONS,
+                        "structs and enums",
+                    ))
+                }
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+// Whether `#[repr(packed)]` or `#[repr(packed(N))]` is among `attrs`, in which
+// case fields can't be borrowed in place.
+pub fn is_packed(attrs: &[Attribute]) -> Result<bool> {
+    for attr in attrs.iter() {
+        if !attr.path.is_ident("repr") {
+            continue;
+        }
+        if let Meta::List(list) = attr.parse_meta()? {
+            let packed = list.nested.iter().any(|nested| match nested {
+                NestedMeta::Meta(Meta::Path(path)) => path.is_ident("packed"),
+                NestedMeta::Meta(Meta::List(list)) => list.path.is_ident("packed"),
+                _ => false,
+            });
+            if packed {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+// Options collected from `#[debug(...)]` attributes on an enum variant.
+#[derive(Default)]
+pub struct VariantAttribute {
+    // from `rename = "..."`, the variant name shown in the output
+    pub rename: Option<LitStr>,
+    // from `#[debug = "..."]`, as for a struct
+    pub format: Option<LitStr>,
+    // from `transparent`, a variant with a single field prints as that field
+    pub transparent: bool,
+}
+
+pub fn parse_variant_attribute(attrs: &[Attribute]) -> Result<VariantAttribute> {
+    let mut parsed = VariantAttribute::default();
+    let mut seen = Vec::new();
+    for attr in attrs.iter() {
+        if !attr.path.is_ident("debug") {
+            continue;
+        }
+        let meta = attr.parse_meta()?;
+        let list = match &meta {
+            Meta::NameValue(MetaNameValue {
+                lit: Lit::Str(format),
+                ..
+            }) => {
+                check_duplicate(&mut seen, "debug = \"...\"".to_owned(), &meta)?;
+                format::parse(format)?;
+                parsed.format = Some(format.clone());
+                continue;
+            }
+            Meta::List(list) => list,
+            _ => return Err(unexpected_meta(&meta)),
+        };
+        for nested in list.nested.iter() {
+            if let Some(name) = option_name(nested) {
+                check_duplicate(&mut seen, name, nested)?;
+            }
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(rename),
+                    ..
+                })) if path.is_ident("rename") => {
+                    parsed.rename = Some(rename.clone());
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("transparent") => {
+                    parsed.transparent = true;
+                }
+                _ => return Err(unexpected_option(nested, VARIANT_OPTIONS, "enum variants")),
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+// How a `#[debug(redact)]` field is replaced in the output.
+pub enum Redact {
+    // `redact`, prints `<redacted>`
+    Placeholder,
+    // `redact = "len"`, prints `<redacted len=N>` using the value's `len()`;
+    // there is no trait to infer a bound from, so a generic field whose `len`
+    // comes from a trait needs `#[debug(bound = "...")]`
+    Len,
+    // `redact = "hash"`, prints `<redacted hash=...>` using the value's `Hash` impl
+    Hash,
+}
+
+// A named format for a field, instead of writing out a format string.
+#[derive(Clone, Copy)]
+pub enum Preset {
+    // `hex`, zero-padded to the width of the integer type, e.g. `0x002a`
+    Hex,
+    // `bin`, zero-padded to the width of the integer type, e.g. `0b00101010`
+    Bin,
+    // `bytes`, an integer byte count as a human-readable size, e.g. `1.5 KiB`
+    Bytes,
+    // `duration`, a `Duration` in hours, minutes and seconds, e.g. `1h 2m 3s`
+    Duration,
+}
+
+impl Preset {
+    fn from_ident(path: &Path) -> Option<Self> {
+        let preset = match path.get_ident()?.to_string().as_str() {
+            "hex" => Preset::Hex,
+            "bin" => Preset::Bin,
+            "bytes" => Preset::Bytes,
+            "duration" => Preset::Duration,
+            _ => return None,
+        };
+        Some(preset)
+    }
+}
+
+// Options collected from `#[debug = "..."]` and `#[debug(...)]` attributes on
+// a field.
+#[derive(Default)]
+pub struct FieldAttribute {
+    // format string applied to the field value, e.g. "0b{:08b}"
+    pub format: Option<LitStr>,
+    // from `hex`, `bin`, `bytes` or `duration`, in place of a format string
+    pub preset: Option<Preset>,
+    // from `pretty_field = "..."`, the format string used instead under `{:#?}`
+    pub pretty_format: Option<LitStr>,
+    // from `skip`, the field is left out of the output
+    pub skip: bool,
+    pub redact: Option<Redact>,
+    // from `with = "path::to::fn"`, a `fn(&T, &mut fmt::Formatter) -> fmt::Result`
+    // which renders the field instead of its Debug impl
+    pub with: Option<Path>,
+    // from `truncate = N`, only the first N elements of an iterable field are
+    // printed, or the first N bytes in hex for a byte buffer
+    pub truncate: Option<usize>,
+    // from `sorted`, entries of a map or set field are printed in key order;
+    // sorted in O(n log n) with the "alloc" feature of derive_debug, and in
+    // O(n²) comparisons without it
+    pub sorted: bool,
+    // from `via = "Type"`, a copy of the field is converted with `Into<Type>`
+    // and the result is printed in its place, format string included
+    pub via: Option<Type>,
+    // from `rename = "..."`, the field name shown in the output
+    pub rename: Option<LitStr>,
+    // from `bound = "..."`, replaces the bounds inferred from this field;
+    // `bound = ""` opts the field out of bound inference altogether
+    pub bound: Option<Punctuated<WherePredicate, Token![,]>>,
+}
+
+impl FieldAttribute {
+    // Whether the field's value is printed through its own `Debug` impl, and
+    // so contributes to the inferred bounds.
+    pub fn uses_debug(&self) -> bool {
+        !self.skip && self.redact.is_none() && self.with.is_none()
+    }
+
+    // Whether the field's value is printed through its own `Debug` impl with
+    // nothing changing how, which other derives may print through a trait of
+    // their own instead.
+    pub fn prints_as_is(&self) -> bool {
+        self.uses_debug()
+            && self.format.is_none()
+            && self.preset.is_none()
+            && self.truncate.is_none()
+            && !self.sorted
+            && self.via.is_none()
+    }
+}
+
+// The format string of `#[debug = "..."]` is passed the field as its only
+// argument, so it has to contain exactly one placeholder, `{}` or `{0}`, which
+// takes neither its width nor its precision from another argument. Checking
+// here points the error into the literal instead of at the derive.
+fn check_field_format(format: &LitStr) -> Result<()> {
+    let placeholders = format::parse(format)?;
+    let placeholder = match placeholders.as_slice() {
+        [placeholder] => placeholder,
+        [] => {
+            return Err(Error::new_spanned(
+                format,
+                "format string has no placeholder for the field, e.g. `{:?}`",
+            ))
+        }
+        [_, extra, ..] => {
+            return Err(Error::new(
+                extra.span,
+                "format string has more than one placeholder, only the field is passed to it",
+            ))
+        }
+    };
+    match placeholder.arg {
+        Argument::Next | Argument::Index(0) => {}
+        _ => {
+            return Err(Error::new(
+                placeholder.span,
+                "the field is the only argument, refer to it as `{}` or `{0}`",
+            ))
+        }
+    }
+    if !placeholder.counts.is_empty() {
+        return Err(Error::new(
+            placeholder.span,
+            "width and precision must be literals, the field is the only argument",
+        ));
+    }
+    Ok(())
+}
+
+pub fn parse_field_attribute(f: &Field) -> Result<FieldAttribute> {
+    let mut parsed = FieldAttribute::default();
+    let mut seen = Vec::new();
+    for attr in f.attrs.iter() {
+        if !attr.path.is_ident("debug") {
+            continue;
+        }
+        let meta = attr.parse_meta()?;
+        let list = match &meta {
+            Meta::NameValue(MetaNameValue {
+                lit: Lit::Str(format),
+                ..
+            }) => {
+                check_duplicate(&mut seen, "debug = \"...\"".to_owned(), &meta)?;
+                check_field_format(format)?;
+                parsed.format = Some(format.clone());
+                continue;
+            }
+            Meta::List(list) => list,
+            _ => return Err(unexpected_meta(&meta)),
+        };
+        for nested in list.nested.iter() {
+            if let Some(name) = option_name(nested) {
+                check_duplicate(&mut seen, name, nested)?;
+            }
+            match nested {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => {
+                    parsed.skip = true;
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("redact") => {
+                    parsed.redact = Some(Redact::Placeholder);
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("sorted") => {
+                    parsed.sorted = true;
+                }
+                NestedMeta::Meta(Meta::Path(path)) if Preset::from_ident(path).is_some() => {
+                    parsed.preset = Preset::from_ident(path);
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(mode),
+                    ..
+                })) if path.is_ident("redact") => {
+                    parsed.redact = Some(match mode.value().as_str() {
+                        "len" => Redact::Len,
+                        "hash" => Redact::Hash,
+                        _ => {
+                            return Err(Error::new_spanned(
+                                mode,
+                                "expected `redact`, `redact = \"len\"` or `redact = \"hash\"`",
+                            ))
+                        }
+                    });
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(with),
+                    ..
+                })) if path.is_ident("with") => {
+                    parsed.with = Some(with.parse()?);
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(via),
+                    ..
+                })) if path.is_ident("via") => {
+                    parsed.via = Some(via.parse()?);
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Int(truncate),
+                    ..
+                })) if path.is_ident("truncate") => {
+                    parsed.truncate = Some(truncate.base10_parse()?);
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(rename),
+                    ..
+                })) if path.is_ident("rename") => {
+                    parsed.rename = Some(rename.clone());
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(bound),
+                    ..
+                })) if path.is_ident("bound") => {
+                    parsed.bound = Some(bound.parse_with(Punctuated::parse_terminated)?);
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(format),
+                    ..
+                })) if path.is_ident("pretty_field") => {
+                    check_field_format(format)?;
+                    parsed.pretty_format = Some(format.clone());
+                }
+                _ => return Err(unexpected_option(nested, FIELD_OPTIONS, "fields")),
+            }
+        }
+    }
+    check_field_conflicts(&seen)?;
+    Ok(parsed)
+}
+
+// Options collected from `#[display = "..."]` and `#[display(...)]` attributes
+// on a struct, enum or variant, for `#[derive(CustomDisplay)]`.
+#[derive(Default)]
+pub struct DisplayAttribute {
+    // format string referring to fields by name, or by index for tuple fields
+    pub format: Option<LitStr>,
+    // from `bound = "..."`, replaces every inferred bound when present
+    pub bound: Option<Punctuated<WherePredicate, Token![,]>>,
+}
+
+pub fn parse_display_attribute(attrs: &[Attribute]) -> Result<DisplayAttribute> {
+    let mut parsed = DisplayAttribute::default();
+    for attr in attrs.iter() {
+        if !attr.path.is_ident("display") {
+            continue;
+        }
+        let meta = attr.parse_meta()?;
+        let unrecognized = || {
+            Error::new_spanned(
+                &meta,
+                "expected `display = \"...\"` or `display(bound = \"...\")`",
+            )
+        };
+        let list = match &meta {
+            Meta::NameValue(MetaNameValue {
+                lit: Lit::Str(format),
+                ..
+            }) => {
+                parsed.format = Some(format.clone());
+                continue;
+            }
+            Meta::List(list) => list,
+            _ => return Err(unrecognized()),
+        };
+        for nested in list.nested.iter() {
+            match nested {
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                    path,
+                    lit: Lit::Str(bound),
+                    ..
+                })) if path.is_ident("bound") => {
+                    parsed.bound = Some(bound.parse_with(Punctuated::parse_terminated)?);
+                }
+                _ => return Err(unrecognized()),
+            }
+        }
+    }
+    Ok(parsed)
+}
@@ -0,0 +1,210 @@
+use crate::attr::{ContainerAttribute, FieldAttribute, Preset, Redact};
+use crate::format;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_quote, visit::Visit, Field, GenericArgument, Generics, Ident, LitStr, PathArguments,
+    Type, TypePath, WherePredicate,
+};
+
+// Walks the field types and records which type parameters need a `Debug`
+// bound. A parameter used only inside `PhantomData<...>` needs none, and one
+// used only through an associated type like `T::Value` or
+// `<T as Trait>::Value` gets a bound on the associated type instead of on
+// itself.
+//
+// Only the parameters are ever bounded, never the field types themselves, so
+// recursive types like `One<T> { two: Option<Box<Two<T>>> }` come out as
+// `T: Debug` rather than as a cycle through `Two<T>: Debug`.
+struct BoundVisitor<'a> {
+    params: Vec<&'a Ident>,
+    bounded_types: Vec<Type>,
+}
+
+impl<'a> BoundVisitor<'a> {
+    fn find_param(&self, ident: &Ident) -> Option<&'a Ident> {
+        self.params.iter().find(|param| ident == **param).copied()
+    }
+
+    // Whether `ty` is exactly one of the type parameters.
+    fn is_param(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Path(TypePath { qself: None, path }) => path
+                .get_ident()
+                .is_some_and(|ident| self.find_param(ident).is_some()),
+            _ => false,
+        }
+    }
+
+    // `T::Value`, `T::Value::Item` or `<T as Trait>::Value`
+    fn is_associated_type(&self, ty: &TypePath) -> bool {
+        match &ty.qself {
+            Some(qself) => self.is_param(&qself.ty),
+            None => {
+                ty.path.segments.len() > 1 && self.find_param(&ty.path.segments[0].ident).is_some()
+            }
+        }
+    }
+}
+
+impl<'a, 'ast> Visit<'ast> for BoundVisitor<'a> {
+    fn visit_type_path(&mut self, ty: &'ast TypePath) {
+        let segments = &ty.path.segments;
+        if segments
+            .last()
+            .is_some_and(|seg| seg.ident == "PhantomData")
+        {
+            return;
+        }
+        let is_param = ty.qself.is_none()
+            && segments.len() == 1
+            && self.find_param(&segments[0].ident).is_some();
+        if is_param || self.is_associated_type(ty) {
+            let ty = Type::Path(ty.clone());
+            if !self.bounded_types.contains(&ty) {
+                self.bounded_types.push(ty);
+            }
+            return;
+        }
+        syn::visit::visit_type_path(self, ty);
+    }
+}
+
+// The type parameters and associated types of type parameters which `ty`
+// mentions, each of which needs to implement the trait being derived.
+pub fn bounded_types(generics: &Generics, ty: &Type) -> Vec<Type> {
+    let mut visitor = BoundVisitor {
+        params: generics.type_params().map(|param| &param.ident).collect(),
+        bounded_types: Vec::new(),
+    };
+    visitor.visit_type(ty);
+    visitor.bounded_types
+}
+
+// `K` in `HashMap<K, V>` or `BTreeSet<K>`, which `#[debug(sorted)]` needs to
+// be Ord.
+fn first_type_argument(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Reference(reference) => first_type_argument(&reference.elem),
+        Type::Path(path) => match &path.path.segments.last()?.arguments {
+            PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            }),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Appends `predicates` to the where clause, skipping ones already present.
+pub fn extend_where_clause(
+    generics: &mut Generics,
+    predicates: impl IntoIterator<Item = WherePredicate>,
+) {
+    let where_clause = generics.make_where_clause();
+    for predicate in predicates {
+        if !where_clause.predicates.iter().any(|p| *p == predicate) {
+            where_clause.predicates.push(predicate);
+        }
+    }
+}
+
+// The fmt trait of the only placeholder in a field format string, which
+// `check_field_format` has already accepted.
+fn format_trait(format: &LitStr) -> TokenStream2 {
+    let placeholders = format::parse(format).unwrap();
+    let fmt_trait = format_ident!("{}", placeholders[0].fmt_trait.name());
+    quote!(core::fmt::#fmt_trait)
+}
+
+// Adds the where-predicates needed by the generated impl. A container
+// `#[debug(bound = "...")]` replaces all of them.
+pub fn add_trait_bounds(
+    mut generics: Generics,
+    fields: &[(&Field, &FieldAttribute)],
+    container: &ContainerAttribute,
+    value_trait: &TokenStream2,
+) -> Generics {
+    let predicates: Vec<WherePredicate> = match &container.bound {
+        Some(bound) => bound.iter().cloned().collect(),
+        None => fields
+            .iter()
+            .flat_map(|(f, attrs)| field_predicates(&generics, f, attrs, value_trait))
+            .collect(),
+    };
+    extend_where_clause(&mut generics, predicates);
+    generics
+}
+
+// The where-predicates printing one field needs. A field's own
+// `#[debug(bound = "...")]` replaces what would be inferred from it.
+//
+// Fields printed as they are need `value_trait`, which is `Debug` except for
+// derives that print through some other trait. Fields with a format string, or
+// a `pretty_field` one, need the fmt trait its placeholder uses, e.g.
+// `T: Binary` for "0b{:08b}", and the others always go through `core::fmt`.
+// The keys of a `sorted` field are also bounded by Ord, and a field redacted
+// with `redact = "hash"` is bounded by Hash instead of any fmt trait.
+pub fn field_predicates(
+    generics: &Generics,
+    f: &Field,
+    attrs: &FieldAttribute,
+    value_trait: &TokenStream2,
+) -> Vec<WherePredicate> {
+    let mut predicates: Vec<WherePredicate> = Vec::new();
+    if let Some(bound) = &attrs.bound {
+        predicates.extend(bound.iter().cloned());
+    } else if let Some(via) = &attrs.via {
+        // The field itself is only cloned and converted; what gets printed is
+        // the converted value.
+        if !bounded_types(generics, &f.ty).is_empty() {
+            let ty = &f.ty;
+            predicates.push(parse_quote! {
+                #ty: core::clone::Clone + core::convert::Into<#via>
+            });
+        }
+        predicates.extend(
+            bounded_types(generics, via)
+                .iter()
+                .map(|ty| -> WherePredicate { parse_quote!(#ty: core::fmt::Debug) }),
+        );
+    } else if let Some(Redact::Hash) = attrs.redact {
+        predicates.extend(
+            bounded_types(generics, &f.ty)
+                .iter()
+                .map(|ty| -> WherePredicate { parse_quote!(#ty: core::hash::Hash) }),
+        );
+    } else if attrs.uses_debug() {
+        let types = bounded_types(generics, &f.ty);
+        let mut bounds = vec![match (attrs.preset, &attrs.format) {
+            (Some(Preset::Hex), _) => quote!(core::fmt::LowerHex),
+            (Some(Preset::Bin), _) => quote!(core::fmt::Binary),
+            (None, Some(format)) if attrs.truncate.is_none() && !attrs.sorted => {
+                format_trait(format)
+            }
+            _ if attrs.prints_as_is() => value_trait.clone(),
+            _ => quote!(core::fmt::Debug),
+        }];
+        if let Some(pretty_format) = &attrs.pretty_format {
+            bounds.push(format_trait(pretty_format));
+        }
+        for bound in bounds {
+            predicates.extend(
+                types
+                    .iter()
+                    .map(|ty| -> WherePredicate { parse_quote!(#ty: #bound) }),
+            );
+        }
+        if attrs.sorted {
+            if let Some(key) = first_type_argument(&f.ty) {
+                predicates.extend(
+                    bounded_types(generics, key)
+                        .iter()
+                        .map(|ty| -> WherePredicate { parse_quote!(#ty: core::cmp::Ord) }),
+                );
+            }
+        }
+    }
+    predicates
+}
@@ -0,0 +1,150 @@
+use crate::attr::{parse_container_attribute, FieldAttribute};
+use crate::bound::{add_trait_bounds, extend_where_clause};
+use crate::display::format_fields;
+use crate::{debug_value, parse_bodies, value_helpers, Body};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse::Result, DeriveInput, Field, Fields, Generics, Ident, WherePredicate};
+
+pub fn expand(ast: DeriveInput) -> Result<TokenStream2> {
+    let ident = &ast.ident;
+    let container = parse_container_attribute(&ast.attrs)?;
+    let bodies = parse_bodies(&ast, &container, "DefmtFormat")?;
+
+    let mut predicates = Vec::new();
+    let arms = bodies
+        .iter()
+        .map(|body| {
+            format_arm(
+                body,
+                container.non_exhaustive,
+                &ast.generics,
+                &mut predicates,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let fields: Vec<(&Field, &FieldAttribute)> =
+        bodies.iter().flat_map(Body::bounded_fields).collect();
+    let mut generics = add_trait_bounds(
+        ast.generics.clone(),
+        &fields,
+        &container,
+        &quote!(defmt::Format),
+    );
+    if container.bound.is_none() {
+        extend_where_clause(&mut generics, predicates);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let helpers = value_helpers(&bodies);
+    let expanded = quote! {
+        impl #impl_generics defmt::Format for #ident #ty_generics #where_clause {
+            fn format(&self, f: defmt::Formatter<'_>) {
+                #helpers
+                match *self {
+                    #( #arms )*
+                }
+            }
+        }
+    };
+    Ok(expanded)
+}
+
+// Generated, for a struct or struct-like variant `Field { name, bitmask }`:
+// Field {
+//     name: ref __self_0,
+//     bitmask: ref __self_1,
+// } => defmt::write!(
+//     f,
+//     "Field {{ name: {}, bitmask: {} }}",
+//     __self_0,
+//     defmt::Debug2Format(&format_args!("0b{:08b}", __self_1)),
+// ),
+//
+// The layout follows what `debug_struct` and `debug_tuple` print. Fields
+// printed as they are go through their own `defmt::Format` impl, and ones with
+// a format string, `with`, `redact` or `truncate` attribute are rendered with
+// `core::fmt` the same way CustomDebug renders them.
+//
+// A struct or variant with its own `#[debug = "..."]` is rendered with
+// `core::fmt` as a whole, the same way as by CustomDebug, and a transparent
+// one as its only field.
+fn format_arm(
+    body: &Body,
+    non_exhaustive: bool,
+    generics: &Generics,
+    predicates: &mut Vec<WherePredicate>,
+) -> Result<TokenStream2> {
+    if let Some(format) = &body.format {
+        let (pattern, args) = format_fields(
+            &body.path,
+            body.fields,
+            Some(&body.field_attrs),
+            format,
+            generics,
+            predicates,
+        )?;
+        return Ok(quote! {
+            #pattern => defmt::write!(f, "{}", defmt::Debug2Format(&#args)),
+        });
+    }
+    let name = escape(&body.name);
+    let (pattern, copies) = body.bind_fields();
+    if body.transparent {
+        let field = body.fields.iter().next().unwrap();
+        let value = format_value(&format_ident!("__self_0"), field, &body.field_attrs[0]);
+        return Ok(quote! {
+            #pattern => {
+                #copies
+                defmt::Format::format(&#value, f)
+            }
+        });
+    }
+
+    let mut pieces = Vec::new();
+    let mut args = Vec::new();
+    for (i, (f, attrs)) in body.fields.iter().zip(body.field_attrs.iter()).enumerate() {
+        if attrs.skip {
+            continue;
+        }
+        let binding = format_ident!("__self_{}", i);
+        args.push(format_value(&binding, f, attrs));
+        pieces.push(match (&attrs.rename, &f.ident) {
+            (Some(rename), _) => format!("{}: {{}}", escape(&rename.value())),
+            (None, Some(ident)) => format!("{}: {{}}", ident),
+            (None, None) => "{}".to_owned(),
+        });
+    }
+    if non_exhaustive {
+        pieces.push("..".to_owned());
+    }
+
+    let format = match body.fields {
+        _ if pieces.is_empty() => name,
+        Fields::Unnamed(_) => format!("{}({})", name, pieces.join(", ")),
+        _ => format!("{} {{{{ {} }}}}", name, pieces.join(", ")),
+    };
+    Ok(quote! {
+        #pattern => {
+            #copies
+            defmt::write!(f, #format #( , #args )*)
+        }
+    })
+}
+
+// The argument passed to `defmt::write!` for one field, `binding` being a
+// reference to it.
+fn format_value(binding: &Ident, f: &Field, attrs: &FieldAttribute) -> TokenStream2 {
+    if attrs.prints_as_is() {
+        quote!(#binding)
+    } else {
+        let value = debug_value(binding, &f.ty, attrs, false);
+        quote!(defmt::Debug2Format(#value))
+    }
+}
+
+// Doubles the braces in a name so that it can be part of a format string.
+fn escape(name: &str) -> String {
+    name.replace('{', "{{").replace('}', "}}")
+}
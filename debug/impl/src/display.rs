@@ -0,0 +1,313 @@
+use crate::attr::{parse_display_attribute, FieldAttribute};
+use crate::bound::{bounded_types, extend_where_clause, field_predicates};
+use crate::debug_value;
+use crate::format::{self, Argument, FmtTrait, Placeholder};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use std::ops::Range;
+use syn::{
+    parse::Result, parse_quote, Data, DeriveInput, Error, Fields, Generics, Ident, LitStr,
+    WherePredicate,
+};
+
+pub fn expand(ast: DeriveInput) -> Result<TokenStream2> {
+    let ident = &ast.ident;
+    let container = parse_display_attribute(&ast.attrs)?;
+
+    let mut generics = ast.generics.clone();
+    let mut predicates = Vec::new();
+    let arms = match &ast.data {
+        Data::Struct(data) => vec![display_arm(
+            quote!(#ident),
+            ident,
+            &data.fields,
+            container.format.as_ref(),
+            &ast.generics,
+            &mut predicates,
+        )?],
+        Data::Enum(data) => {
+            if let Some(format) = &container.format {
+                return Err(Error::new_spanned(
+                    format,
+                    "put `#[display = \"...\"]` on each variant of an enum",
+                ));
+            }
+            data.variants
+                .iter()
+                .map(|variant| {
+                    let variant_attr = parse_display_attribute(&variant.attrs)?;
+                    if let Some(bound) = &variant_attr.bound {
+                        return Err(Error::new_spanned(
+                            bound,
+                            "`display(bound = \"...\")` is only accepted on the enum",
+                        ));
+                    }
+                    let variant_ident = &variant.ident;
+                    display_arm(
+                        quote!(#ident::#variant_ident),
+                        variant_ident,
+                        &variant.fields,
+                        variant_attr.format.as_ref(),
+                        &ast.generics,
+                        &mut predicates,
+                    )
+                })
+                .collect::<Result<_>>()?
+        }
+        Data::Union(data) => {
+            return Err(Error::new_spanned(
+                data.union_token,
+                "CustomDisplay cannot be derived for unions",
+            ))
+        }
+    };
+    match &container.bound {
+        Some(bound) => extend_where_clause(&mut generics, bound.iter().cloned()),
+        None => extend_where_clause(&mut generics, predicates),
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let expanded = quote! {
+        impl #impl_generics core::fmt::Display for #ident #ty_generics #where_clause {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match *self {
+                    #( #arms )*
+                }
+            }
+        }
+    };
+    Ok(expanded)
+}
+
+// Generated, for `#[display = "{name} ({bitmask:08b})"] Field { name, bitmask }`:
+// Field {
+//     name: ref __self_0,
+//     bitmask: ref __self_1,
+// } => f.write_fmt(format_args!(
+//     "{name} ({bitmask:08b})",
+//     name = __self_0,
+//     bitmask = __self_1,
+// )),
+//
+// Unit structs and variants without a format print their name.
+fn display_arm(
+    path: TokenStream2,
+    ident: &Ident,
+    fields: &Fields,
+    format: Option<&LitStr>,
+    generics: &Generics,
+    predicates: &mut Vec<WherePredicate>,
+) -> Result<TokenStream2> {
+    let format = match format {
+        Some(format) => format,
+        None if fields.is_empty() => {
+            let name = ident.to_string();
+            let pattern = match fields {
+                Fields::Named(_) => quote!(#path {}),
+                Fields::Unnamed(_) => quote!(#path()),
+                Fields::Unit => quote!(#path),
+            };
+            return Ok(quote! {
+                #pattern => f.write_str(#name),
+            });
+        }
+        None => {
+            return Err(Error::new_spanned(
+                ident,
+                "missing `#[display = \"...\"]` for CustomDisplay",
+            ))
+        }
+    };
+    let (pattern, args) = format_fields(&path, fields, None, format, generics, predicates)?;
+    Ok(quote! {
+        #pattern => f.write_fmt(#args),
+    })
+}
+
+// The pattern binding every field of a struct or variant, and the
+// `format_args!` call applying `format` to them. Also used by CustomDebug for
+// `#[debug = "..."]` on a struct or variant.
+//
+// Only the fields the format string refers to are passed, by name. Positional
+// arguments that are never used are an error, so for tuple fields every `{0}`,
+// `{}`, `1$` or `.*` in the string is rewritten to name the field's binding:
+// `"{1}"` becomes `"{__self_1}"`. Every type parameter in a referenced field's
+// type gets bounded by the fmt trait its placeholder uses, e.g. `T: LowerHex`
+// for `{value:x}`.
+//
+// CustomDebug passes the `#[debug(...)]` attributes of the fields along, so
+// that a struct format can't print what the field attributes hide: a skipped
+// field can't be referred to, and a field printed differently than by its own
+// Debug impl, like a redacted one, is passed the way `debug_value` prints it
+// and has to be formatted with `{:?}`.
+pub fn format_fields(
+    path: &TokenStream2,
+    fields: &Fields,
+    field_attrs: Option<&[FieldAttribute]>,
+    format: &LitStr,
+    generics: &Generics,
+    predicates: &mut Vec<WherePredicate>,
+) -> Result<(TokenStream2, TokenStream2)> {
+    let placeholders = format::parse(format)?;
+
+    let bindings: Vec<Ident> = (0..fields.len())
+        .map(|i| format_ident!("__self_{}", i))
+        .collect();
+    // which field each argument of the format string resolves to, if any
+    let field_index = |arg: &Argument, next: &mut usize| -> Option<usize> {
+        match (arg, fields) {
+            (Argument::Name(name), Fields::Named(_)) => fields
+                .iter()
+                .position(|f| f.ident.as_ref().unwrap() == name),
+            (Argument::Index(index), Fields::Unnamed(_)) => Some(*index),
+            (Argument::Next, Fields::Unnamed(_)) => {
+                *next += 1;
+                Some(*next - 1)
+            }
+            _ => None,
+        }
+    };
+
+    let field_name = |index: usize| match &fields.iter().nth(index).unwrap().ident {
+        Some(ident) => format!("`{}`", ident),
+        None => format!("field {}", index),
+    };
+
+    let mut referenced = Vec::new();
+    // referenced fields passed as `debug_value` prints them
+    let mut through_attrs = Vec::new();
+    // the ranges of the format string that refer to a tuple field, and the
+    // text naming its binding that replaces each
+    let mut renames: Vec<(Range<usize>, String)> = Vec::new();
+    let mut next = 0;
+    for Placeholder {
+        arg,
+        arg_range,
+        fmt_trait,
+        counts,
+        span,
+    } in placeholders.iter()
+    {
+        let mut refer = |arg: &Argument, range: &Range<usize>, suffix: &str| {
+            let index = field_index(arg, &mut next)?;
+            if let (Fields::Unnamed(_), Some(binding)) = (fields, bindings.get(index)) {
+                renames.push((range.clone(), format!("{}{}", binding, suffix)));
+            }
+            Some(index)
+        };
+        let mut references = Vec::new();
+        // counts are taken before the value, as with `{:.*}`
+        for (count, range) in counts {
+            references.extend(refer(count, range, "$"));
+        }
+        let index = refer(arg, arg_range, "");
+        for index in references.iter().chain(&index) {
+            if *index >= fields.len() {
+                return Err(Error::new(*span, format!("there is no field {}", index)));
+            }
+            if field_attrs.is_some_and(|attrs| attrs[*index].skip) {
+                return Err(Error::new(
+                    *span,
+                    format!(
+                        "{} is skipped by `#[debug(skip)]` and can't be printed",
+                        field_name(*index),
+                    ),
+                ));
+            }
+            referenced.push(*index);
+        }
+        if let Some(index) = index {
+            let f = fields.iter().nth(index).unwrap();
+            match field_attrs.map(|attrs| &attrs[index]) {
+                Some(attrs) if !attrs.prints_as_is() => {
+                    if *fmt_trait != FmtTrait::Debug {
+                        return Err(Error::new(
+                            *span,
+                            format!(
+                                "{} is printed through its `#[debug(...)]` options, so format it with `:?`",
+                                field_name(index),
+                            ),
+                        ));
+                    }
+                    through_attrs.push(index);
+                    predicates.extend(field_predicates(
+                        generics,
+                        f,
+                        attrs,
+                        &quote!(core::fmt::Debug),
+                    ));
+                }
+                Some(FieldAttribute {
+                    bound: Some(bound), ..
+                }) => predicates.extend(bound.iter().cloned()),
+                _ => {
+                    let fmt_trait = format_ident!("{}", fmt_trait.name());
+                    predicates.extend(
+                        bounded_types(generics, &f.ty)
+                            .iter()
+                            .map(|ty| -> WherePredicate {
+                                parse_quote!(#ty: core::fmt::#fmt_trait)
+                            }),
+                    );
+                }
+            }
+        }
+    }
+
+    let values: Vec<TokenStream2> = fields
+        .iter()
+        .zip(bindings.iter())
+        .enumerate()
+        .map(|(i, (f, binding))| match field_attrs {
+            Some(attrs) if through_attrs.contains(&i) => {
+                debug_value(binding, &f.ty, &attrs[i], false)
+            }
+            _ => quote!(#binding),
+        })
+        .collect();
+    let (pattern, args) = match fields {
+        Fields::Named(named) => {
+            let field_idents: Vec<&Ident> = named
+                .named
+                .iter()
+                .map(|f| f.ident.as_ref().unwrap())
+                .collect();
+            let args = field_idents
+                .iter()
+                .zip(values.iter())
+                .enumerate()
+                .filter(|(i, _)| referenced.contains(i))
+                .map(|(_, (ident, value))| quote!(#ident = #value));
+            let args = quote!(#( #args ),*);
+            (quote!(#path { #( #field_idents: ref #bindings ),* }), args)
+        }
+        Fields::Unnamed(_) => {
+            let args = bindings
+                .iter()
+                .zip(values.iter())
+                .enumerate()
+                .filter(|(i, _)| referenced.contains(i))
+                .map(|(_, (binding, value))| quote!(#binding = #value));
+            let args = quote!(#( #args ),*);
+            (quote!(#path( #( ref #bindings ),* )), args)
+        }
+        Fields::Unit => (quote!(#path), quote!()),
+    };
+    let format = if renames.is_empty() {
+        format.clone()
+    } else {
+        let value = format.value();
+        let mut renamed = String::new();
+        let mut copied = 0;
+        // counts were visited first but are written after the argument
+        renames.sort_by_key(|(range, _)| range.start);
+        for (range, name) in renames {
+            renamed.push_str(&value[copied..range.start]);
+            renamed.push_str(&name);
+            copied = range.end;
+        }
+        renamed.push_str(&value[copied..]);
+        LitStr::new(&renamed, format.span())
+    };
+    Ok((pattern, quote!(format_args!(#format, #args))))
+}
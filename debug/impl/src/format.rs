@@ -0,0 +1,239 @@
+// A small parser for the format strings accepted by `format_args!`, enough to
+// tell which arguments a string refers to and through which fmt trait.
+//
+// https://doc.rust-lang.org/std/fmt/index.html#syntax
+
+use proc_macro2::Span;
+use std::ops::Range;
+use syn::{parse::Result, Error, LitStr};
+
+// The argument a placeholder, width or precision refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Argument {
+    // `{}`, the next positional argument
+    Next,
+    // `{0}`
+    Index(usize),
+    // `{name}`
+    Name(String),
+}
+
+// An argument used as a width or precision, with the range of the `width$`,
+// `precision$` or `*` in the format string that refers to it.
+pub type Count = (Argument, Range<usize>);
+
+// The formatting trait selected by the type at the end of a format spec.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FmtTrait {
+    Display,
+    Debug,
+    LowerHex,
+    UpperHex,
+    Octal,
+    Binary,
+    LowerExp,
+    UpperExp,
+}
+
+impl FmtTrait {
+    pub fn name(self) -> &'static str {
+        match self {
+            FmtTrait::Display => "Display",
+            FmtTrait::Debug => "Debug",
+            FmtTrait::LowerHex => "LowerHex",
+            FmtTrait::UpperHex => "UpperHex",
+            FmtTrait::Octal => "Octal",
+            FmtTrait::Binary => "Binary",
+            FmtTrait::LowerExp => "LowerExp",
+            FmtTrait::UpperExp => "UpperExp",
+        }
+    }
+}
+
+// One `{...}` in a format string.
+#[derive(Debug)]
+pub struct Placeholder {
+    pub arg: Argument,
+    // where the argument is written in the format string, empty for `{}`
+    pub arg_range: Range<usize>,
+    pub fmt_trait: FmtTrait,
+    // arguments used as `width$` or `.precision$`, and `.*` as `Argument::Next`
+    pub counts: Vec<Count>,
+    // the placeholder within the literal where the compiler supports it,
+    // otherwise the whole literal
+    pub span: Span,
+}
+
+// The span of `range` within `lit`, or of the whole literal when the compiler
+// can't point into it or escapes make byte offsets in the value differ from
+// those in the source.
+fn subspan(lit: &LitStr, range: Range<usize>) -> Span {
+    let token = lit.token();
+    if token.to_string() != format!("\"{}\"", lit.value()) {
+        return lit.span();
+    }
+    token
+        .subspan(range.start + 1..range.end + 1)
+        .unwrap_or_else(|| lit.span())
+}
+
+pub fn parse(lit: &LitStr) -> Result<Vec<Placeholder>> {
+    let format = lit.value();
+    let error = |range: Range<usize>, message: String| Error::new(subspan(lit, range), message);
+    let mut placeholders = Vec::new();
+    let mut chars = format.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '{' if chars.peek().map(|&(_, c)| c) == Some('{') => {
+                chars.next();
+            }
+            '}' if chars.peek().map(|&(_, c)| c) == Some('}') => {
+                chars.next();
+            }
+            '}' => {
+                return Err(error(
+                    start..start + 1,
+                    "unmatched `}` in format string, use `}}` for a literal brace".to_owned(),
+                ));
+            }
+            '{' => {
+                let end = match chars.by_ref().find(|&(_, c)| c == '}') {
+                    Some((end, _)) => end,
+                    None => {
+                        return Err(error(
+                            start..format.len(),
+                            "unterminated `{` in format string, use `{{` for a literal brace"
+                                .to_owned(),
+                        ))
+                    }
+                };
+                let range = start..end + 1;
+                let inner = &format[start + 1..end];
+                let (arg, spec) = match inner.find(':') {
+                    Some(colon) => (&inner[..colon], &inner[colon + 1..]),
+                    None => (inner, ""),
+                };
+                let arg_range = start + 1..start + 1 + arg.len();
+                let spec_start = arg_range.end + 1;
+                let arg = parse_argument(arg.trim()).ok_or_else(|| {
+                    error(range.clone(), format!("invalid format argument `{}`", arg))
+                })?;
+                let (fmt_trait, mut counts) = parse_spec(spec).ok_or_else(|| {
+                    error(range.clone(), format!("unknown format spec `{}`", spec))
+                })?;
+                for (_, count_range) in counts.iter_mut() {
+                    *count_range = spec_start + count_range.start..spec_start + count_range.end;
+                }
+                placeholders.push(Placeholder {
+                    arg,
+                    arg_range,
+                    fmt_trait,
+                    counts,
+                    span: subspan(lit, range),
+                });
+            }
+            _ => {}
+        }
+    }
+    Ok(placeholders)
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+        && s != "_"
+}
+
+fn parse_argument(arg: &str) -> Option<Argument> {
+    if arg.is_empty() {
+        Some(Argument::Next)
+    } else if let Ok(index) = arg.parse() {
+        Some(Argument::Index(index))
+    } else if is_identifier(arg) {
+        Some(Argument::Name(arg.to_owned()))
+    } else {
+        None
+    }
+}
+
+// format_spec := [[fill]align][sign]['#']['0'][width]['.' precision]type
+//
+// The ranges of the counts are relative to `spec`.
+fn parse_spec(spec: &str) -> Option<(FmtTrait, Vec<Count>)> {
+    let mut rest = spec;
+    let mut counts = Vec::new();
+
+    let mut chars = rest.chars();
+    let first = chars.next();
+    let second = chars.next();
+    if second.is_some_and(|c| "<^>".contains(c)) {
+        rest = &rest[first.unwrap().len_utf8() + 1..];
+    } else if first.is_some_and(|c| "<^>".contains(c)) {
+        rest = &rest[1..];
+    }
+    rest = rest.strip_prefix(['+', '-']).unwrap_or(rest);
+    rest = rest.strip_prefix('#').unwrap_or(rest);
+    if rest.starts_with('0') && !rest[1..].starts_with('$') {
+        rest = &rest[1..];
+    }
+
+    // where `rest` starts within `spec`
+    let offset = |rest: &str| spec.len() - rest.len();
+
+    // width, either a literal or an argument followed by `$`
+    let (width, after) = parse_count(rest);
+    if let Some(width) = width {
+        counts.extend(width.map(|arg| (arg, offset(rest)..offset(after))));
+        rest = after;
+    }
+
+    if let Some(after) = rest.strip_prefix('.') {
+        rest = after;
+        if let Some(after) = rest.strip_prefix('*') {
+            counts.push((Argument::Next, offset(rest)..offset(after)));
+            rest = after;
+        } else {
+            let (precision, after) = parse_count(rest);
+            counts.extend(precision?.map(|arg| (arg, offset(rest)..offset(after))));
+            rest = after;
+        }
+    }
+
+    let fmt_trait = match rest {
+        "" => FmtTrait::Display,
+        "?" | "x?" | "X?" => FmtTrait::Debug,
+        "x" => FmtTrait::LowerHex,
+        "X" => FmtTrait::UpperHex,
+        "o" => FmtTrait::Octal,
+        "b" => FmtTrait::Binary,
+        "e" => FmtTrait::LowerExp,
+        "E" => FmtTrait::UpperExp,
+        _ => return None,
+    };
+    Some((fmt_trait, counts))
+}
+
+// count := integer | (integer | identifier) '$'
+//
+// Returns `Some(None)` for a literal count, `Some(Some(arg))` for one taken
+// from an argument, and `None` when `s` doesn't start with a count.
+fn parse_count(s: &str) -> (Option<Option<Argument>>, &str) {
+    let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    if digits > 0 {
+        let (index, after) = s.split_at(digits);
+        return match after.strip_prefix('$') {
+            Some(after) => (Some(index.parse().ok().map(Argument::Index)), after),
+            None => (Some(None), after),
+        };
+    }
+    let len = s
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(s.len());
+    let (word, after) = s.split_at(len);
+    match after.strip_prefix('$') {
+        Some(after) if is_identifier(word) => (Some(Some(Argument::Name(word.to_owned()))), after),
+        // a trailing word without `$` is the type, e.g. the `x` of `{:x}`
+        _ => (None, s),
+    }
+}
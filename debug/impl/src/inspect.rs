@@ -0,0 +1,91 @@
+use crate::attr::{parse_container_attribute, FieldAttribute};
+use crate::bound::add_trait_bounds;
+use crate::{debug_value, parse_bodies, value_helpers, Body};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse::Result, Data, DeriveInput, Field};
+
+pub fn expand(ast: DeriveInput) -> Result<TokenStream2> {
+    let ident = &ast.ident;
+    let container = parse_container_attribute(&ast.attrs)?;
+    let bodies = parse_bodies(&ast, &container, "Inspect")?;
+
+    let fields: Vec<(&Field, &FieldAttribute)> = bodies
+        .iter()
+        .flat_map(|body| body.fields.iter().zip(body.field_attrs.iter()))
+        .collect();
+    let generics = add_trait_bounds(
+        ast.generics.clone(),
+        &fields,
+        &container,
+        &quote!(core::fmt::Debug),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let helpers = value_helpers(&bodies);
+    let is_enum = matches!(ast.data, Data::Enum(_));
+    let arms = bodies.iter().map(|body| inspect_arm(body, is_enum));
+    let expanded = quote! {
+        impl #impl_generics derive_debug::Inspect for #ident #ty_generics #where_clause {
+            fn inspect(&self, visitor: &mut dyn derive_debug::Visitor) {
+                #helpers
+                match *self {
+                    #( #arms )*
+                }
+            }
+        }
+    };
+    Ok(expanded)
+}
+
+// Generated, for a variant `Field { name, bitmask }` of enum `Entry`:
+// Entry::Field {
+//     name: ref __self_0,
+//     bitmask: ref __self_1,
+// } => {
+//     visitor.visit_variant("Field");
+//     visitor.visit_field("name", core::any::type_name::<&str>(), __self_0);
+//     visitor.visit_field(
+//         "bitmask",
+//         core::any::type_name::<u8>(),
+//         &format_args!("0b{:08b}", __self_1),
+//     );
+// }
+//
+// Structs leave out `visit_variant`, tuple fields are named "0", "1", ... and
+// skipped fields are neither bound nor visited.
+fn inspect_arm(body: &Body, is_enum: bool) -> TokenStream2 {
+    let name = &body.name;
+    let (pattern, copies) = body.bind_fields();
+    let visit_variant = if is_enum {
+        quote!(visitor.visit_variant(#name);)
+    } else {
+        quote!()
+    };
+    let visits = body
+        .fields
+        .iter()
+        .zip(body.field_attrs.iter())
+        .enumerate()
+        .filter(|(_, (_, attrs))| !attrs.skip)
+        .map(|(i, (f, attrs))| {
+            let field_name = match (&attrs.rename, &f.ident) {
+                (Some(rename), _) => rename.value(),
+                (None, Some(ident)) => ident.to_string(),
+                (None, None) => i.to_string(),
+            };
+            let ty = &f.ty;
+            let binding = format_ident!("__self_{}", i);
+            let value = debug_value(&binding, ty, attrs, false);
+            quote! {
+                visitor.visit_field(#field_name, core::any::type_name::<#ty>(), #value);
+            }
+        });
+    quote! {
+        #pattern => {
+            #copies
+            #visit_variant
+            #( #visits )*
+        }
+    }
+}
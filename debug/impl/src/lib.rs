@@ -0,0 +1,940 @@
+extern crate proc_macro;
+
+mod attr;
+mod bound;
+mod defmt;
+mod display;
+mod format;
+mod inspect;
+
+use crate::attr::{
+    is_packed, parse_container_attribute, parse_field_attribute, parse_variant_attribute,
+    ContainerAttribute, FieldAttribute, Preset, Redact,
+};
+use crate::bound::{add_trait_bounds, extend_where_clause};
+use crate::display::format_fields;
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    parse::Result, parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Field, Fields,
+    GenericArgument, Generics, Ident, Index, LitStr, Member, PathArguments, Type, WherePredicate,
+};
+
+#[proc_macro_derive(CustomDebug, attributes(debug))]
+pub fn derive(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    expand(ast)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(CustomDisplay, attributes(display))]
+pub fn derive_display(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    display::expand(ast)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(Inspect, attributes(debug))]
+pub fn derive_inspect(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    inspect::expand(ast)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+// Exported from derive_debug only with its "defmt" feature, since the
+// generated impl needs the defmt crate.
+#[proc_macro_derive(DefmtFormat, attributes(debug))]
+pub fn derive_defmt(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    defmt::expand(ast)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+// The struct itself or one enum variant, together with the parsed attributes
+// of its fields.
+struct Body<'a> {
+    // pattern path, `Name` for a struct and `Name::Variant` for a variant
+    path: TokenStream2,
+    // name shown in the output, the identifier unless renamed
+    name: String,
+    fields: &'a Fields,
+    field_attrs: Vec<FieldAttribute>,
+    // from `#[debug = "..."]` on the struct or variant, replaces the usual
+    // output altogether
+    format: Option<LitStr>,
+    // from `#[debug(transparent)]`, prints the only field by itself
+    transparent: bool,
+    // a `#[repr(packed)]` struct, whose fields can't be borrowed in place
+    packed: bool,
+}
+
+impl<'a> Body<'a> {
+    fn new(
+        path: TokenStream2,
+        name: String,
+        fields: &'a Fields,
+        format: Option<LitStr>,
+        transparent: bool,
+        packed: bool,
+    ) -> Result<Self> {
+        let field_attrs: Vec<FieldAttribute> = fields
+            .iter()
+            .map(parse_field_attribute)
+            .collect::<Result<_>>()?;
+        if transparent && (field_attrs.len() != 1 || field_attrs[0].skip) {
+            // the struct or variant identifier at the end of the path
+            let ident = path.clone().into_iter().last().unwrap();
+            return Err(Error::new(
+                ident.span(),
+                "`debug(transparent)` requires exactly one field, which is not skipped",
+            ));
+        }
+        if let Some(format) = &format {
+            if transparent {
+                return Err(Error::new_spanned(
+                    format,
+                    "`debug = \"...\"` and `debug(transparent)` cannot be combined",
+                ));
+            }
+            if packed {
+                return Err(Error::new_spanned(
+                    format,
+                    "`debug = \"...\"` is not supported on packed structs",
+                ));
+            }
+        }
+        Ok(Body {
+            path,
+            name,
+            fields,
+            field_attrs,
+            format,
+            transparent,
+            packed,
+        })
+    }
+
+    // The fields, with their attributes, whose Debug impls this body's output
+    // uses; none when a format string replaces the output.
+    fn bounded_fields(&self) -> impl Iterator<Item = (&'a Field, &FieldAttribute)> {
+        let fields = if self.format.is_some() {
+            None
+        } else {
+            Some(self.fields.iter().zip(self.field_attrs.iter()))
+        };
+        fields.into_iter().flatten()
+    }
+
+    // The pattern matching `*self` against this struct or variant, binding a
+    // reference to every shown field as `__self_<i>`, together with statements
+    // to run before the bindings are used.
+    //
+    // References into a packed struct may be unaligned, so there the pattern
+    // binds nothing and the statements copy each shown field out instead:
+    // `let __self_0 = &{ self.name };`. A field that isn't Copy fails the
+    // `assert_copy` call spanned on its type.
+    fn bind_fields(&self) -> (TokenStream2, TokenStream2) {
+        let path = &self.path;
+        if self.packed {
+            let pattern = match self.fields {
+                Fields::Named(_) => quote!(#path { .. }),
+                Fields::Unnamed(_) => quote!(#path(..)),
+                Fields::Unit => quote!(#path),
+            };
+            let copies = self
+                .fields
+                .iter()
+                .zip(self.field_attrs.iter())
+                .enumerate()
+                .filter(|(_, (_, attrs))| !attrs.skip)
+                .map(|(i, (f, _))| {
+                    let member = match &f.ident {
+                        Some(ident) => Member::Named(ident.clone()),
+                        None => Member::Unnamed(Index::from(i)),
+                    };
+                    let binding = format_ident!("__self_{}", i);
+                    let assert_copy = quote_spanned! {f.ty.span()=>
+                        assert_copy(core::ptr::addr_of!(self.#member));
+                    };
+                    quote! {
+                        #assert_copy
+                        let #binding = &{ self.#member };
+                    }
+                })
+                .collect::<Vec<_>>();
+            if copies.is_empty() {
+                return (pattern, quote!());
+            }
+            let copies = quote! {
+                fn assert_copy<T: core::marker::Copy>(_: *const T) {}
+                #( #copies )*
+            };
+            return (pattern, copies);
+        }
+
+        let bindings = self.field_attrs.iter().enumerate().map(|(i, attrs)| {
+            if attrs.skip {
+                quote!(_)
+            } else {
+                let binding = format_ident!("__self_{}", i);
+                quote!(ref #binding)
+            }
+        });
+        let pattern = match self.fields {
+            Fields::Named(named) => {
+                let field_idents = named.named.iter().map(|f| f.ident.as_ref().unwrap());
+                quote!(#path { #( #field_idents: #bindings ),* })
+            }
+            Fields::Unnamed(_) => quote!(#path( #( #bindings ),* )),
+            Fields::Unit => quote!(#path),
+        };
+        (pattern, quote!())
+    }
+}
+
+fn expand(ast: DeriveInput) -> Result<TokenStream2> {
+    let ident = &ast.ident;
+    let container = parse_container_attribute(&ast.attrs)?;
+    if let Data::Union(_) = ast.data {
+        return expand_union(&ast, &container);
+    }
+    if let Some(with) = &container.with {
+        return Err(Error::new_spanned(
+            with,
+            "`debug(with = \"...\")` on the container is only supported for unions",
+        ));
+    }
+    let bodies = parse_bodies(&ast, &container, "CustomDebug")?;
+
+    let mut predicates = Vec::new();
+    let arms = bodies
+        .iter()
+        .map(|body| {
+            debug_arm(
+                body,
+                container.non_exhaustive,
+                &ast.generics,
+                &mut predicates,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let fields: Vec<(&Field, &FieldAttribute)> =
+        bodies.iter().flat_map(Body::bounded_fields).collect();
+    let mut generics = add_trait_bounds(
+        ast.generics.clone(),
+        &fields,
+        &container,
+        &quote!(core::fmt::Debug),
+    );
+    if container.bound.is_none() {
+        extend_where_clause(&mut generics, predicates);
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let helpers = value_helpers(&bodies);
+
+    // Formatting `self` again through `{:?}` gets a fresh formatter without
+    // the alternate flag, so neither this value nor anything nested in it is
+    // spread over several lines.
+    let compact = if container.compact {
+        quote! {
+            if f.alternate() {
+                return f.write_fmt(format_args!("{:?}", self));
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    // The guard lives in the derive_debug crate, as a proc-macro crate can't
+    // export anything but macros. It is held until the value is printed, so
+    // that the values nested in it see it on the stack. It is taken after the
+    // `compact` check, which prints `self` again.
+    let guard = match &container.max_depth {
+        Some(max_depth) if !cfg!(feature = "std") => {
+            return Err(Error::new_spanned(
+                max_depth,
+                "`debug(max_depth = N)` requires the \"std\" feature of derive_debug",
+            ));
+        }
+        Some(max_depth) => quote! {
+            let __guard = match ::derive_debug::__private::enter(
+                self as *const Self as *const (),
+                core::any::type_name::<Self>(),
+                #max_depth,
+            ) {
+                ::derive_debug::__private::Enter::Entered(guard) => guard,
+                ::derive_debug::__private::Enter::Cycle => return f.write_str("<cycle>"),
+                ::derive_debug::__private::Enter::TooDeep => return f.write_str("..."),
+            };
+        },
+        None => quote!(),
+    };
+
+    let expanded = quote! {
+        impl #impl_generics core::fmt::Debug for #ident #ty_generics #where_clause {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                #compact
+                #guard
+                #helpers
+                match *self {
+                    #( #arms )*
+                }
+            }
+        }
+    };
+    Ok(expanded)
+}
+
+// Which field of a union is active can't be known, so a union is printed by
+// the container's `#[debug(with = "...")]` function, or as an opaque
+// `<union>` without one. Neither needs bounds beyond a `debug(bound)`.
+fn expand_union(ast: &DeriveInput, container: &ContainerAttribute) -> Result<TokenStream2> {
+    let ident = &ast.ident;
+    let mut generics = ast.generics.clone();
+    if let Some(bound) = &container.bound {
+        extend_where_clause(&mut generics, bound.iter().cloned());
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let body = match &container.with {
+        Some(with) => quote!(#with(self, f)),
+        None => quote!(f.write_str("<union>")),
+    };
+    Ok(quote! {
+        impl #impl_generics core::fmt::Debug for #ident #ty_generics #where_clause {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                #body
+            }
+        }
+    })
+}
+
+// The struct, or every variant of the enum, together with its field
+// attributes. `derive` names the derive in the error for unions.
+//
+// The compiler has already evaluated `#[cfg]` and `#[cfg_attr]` on the input,
+// so fields and variants compiled out never show up here, and an enabled
+// `#[cfg_attr(feature = "x", debug = "...")]` arrives as `#[debug = "..."]`.
+// Nothing may assume a field list beyond what the input contains.
+fn parse_bodies<'a>(
+    ast: &'a DeriveInput,
+    container: &ContainerAttribute,
+    derive: &str,
+) -> Result<Vec<Body<'a>>> {
+    let ident = &ast.ident;
+    if let Data::Enum(_) = ast.data {
+        if let Some(format) = &container.format {
+            return Err(Error::new_spanned(
+                format,
+                "put `#[debug = \"...\"]` on each variant of an enum",
+            ));
+        }
+        if container.transparent {
+            return Err(Error::new_spanned(
+                ident,
+                "put `#[debug(transparent)]` on each variant of an enum",
+            ));
+        }
+        // only the variant names are ever printed
+        if let Some(rename) = &container.rename {
+            return Err(Error::new_spanned(
+                rename,
+                "put `#[debug(rename = \"...\")]` on the variants of an enum",
+            ));
+        }
+    }
+    let bodies = match &ast.data {
+        Data::Struct(data) => {
+            let name = match &container.rename {
+                Some(rename) => rename.value(),
+                None => ident.to_string(),
+            };
+            vec![Body::new(
+                quote!(#ident),
+                name,
+                &data.fields,
+                container.format.clone(),
+                container.transparent,
+                is_packed(&ast.attrs)?,
+            )?]
+        }
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| {
+                let variant_ident = &variant.ident;
+                let path = quote!(#ident::#variant_ident);
+                let variant_attr = parse_variant_attribute(&variant.attrs)?;
+                let name = match &variant_attr.rename {
+                    Some(rename) => rename.value(),
+                    None => variant_ident.to_string(),
+                };
+                Body::new(
+                    path,
+                    name,
+                    &variant.fields,
+                    variant_attr.format,
+                    variant_attr.transparent,
+                    false,
+                )
+            })
+            .collect::<Result<_>>()?,
+        Data::Union(data) => {
+            return Err(Error::new_spanned(
+                data.union_token,
+                format!("{} cannot be derived for unions", derive),
+            ))
+        }
+    };
+    Ok(bodies)
+}
+
+// Definitions used by the values `debug_value` generates, emitted at the start
+// of the function body when any field needs them. They are in scope for the
+// whole body, field types included, so their names start with `__Debug` to
+// stay clear of the caller's own types.
+fn value_helpers(bodies: &[Body]) -> TokenStream2 {
+    // Lets a `#[debug(with = "...")]` function stand in for the field's own
+    // Debug impl wherever a `&dyn Debug` is expected.
+    let uses_with = bodies
+        .iter()
+        .any(|body| body.field_attrs.iter().any(|attrs| attrs.with.is_some()));
+    let debug_with = if uses_with {
+        quote! {
+            struct __DebugWith<'a, T: ?Sized>(
+                &'a T,
+                fn(&T, &mut core::fmt::Formatter<'_>) -> core::fmt::Result,
+            );
+            impl<'a, T: ?Sized> core::fmt::Debug for __DebugWith<'a, T> {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    (self.1)(self.0, f)
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    // Prints at most `.1` elements of a `#[debug(truncate = N)]` field, as a
+    // list of the elements or as hex for a byte buffer.
+    let uses_truncate = bodies.iter().any(|body| {
+        body.field_attrs
+            .iter()
+            .any(|attrs| attrs.truncate.is_some())
+    });
+    let truncate = if uses_truncate {
+        quote! {
+            struct __DebugTruncate<'a, T: ?Sized>(&'a T, usize);
+            impl<'a, T: ?Sized> core::fmt::Debug for __DebugTruncate<'a, T>
+            where
+                &'a T: core::iter::IntoIterator,
+                <&'a T as core::iter::IntoIterator>::Item: core::fmt::Debug,
+            {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    let mut iter = core::iter::IntoIterator::into_iter(self.0);
+                    let mut list = f.debug_list();
+                    list.entries(iter.by_ref().take(self.1));
+                    let more = iter.count();
+                    if more > 0 {
+                        list.entry(&format_args!("... ({} more)", more));
+                    }
+                    list.finish()
+                }
+            }
+            struct __DebugTruncateHex<'a, T: ?Sized>(&'a T, usize);
+            impl<'a, T: ?Sized> core::fmt::Debug for __DebugTruncateHex<'a, T>
+            where
+                &'a T: core::iter::IntoIterator<Item = &'a u8>,
+            {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    let mut iter = core::iter::IntoIterator::into_iter(self.0);
+                    f.write_str("0x")?;
+                    for byte in iter.by_ref().take(self.1) {
+                        f.write_fmt(format_args!("{:02x}", byte))?;
+                    }
+                    let more = iter.count();
+                    if more > 0 {
+                        f.write_fmt(format_args!(" ... ({} more)", more))?;
+                    }
+                    core::result::Result::Ok(())
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    // 64-bit FNV-1a for `#[debug(redact = "hash")]`, since the hashers in std
+    // are not available without it.
+    let uses_hash = bodies.iter().any(|body| {
+        body.field_attrs
+            .iter()
+            .any(|attrs| matches!(attrs.redact, Some(Redact::Hash)))
+    });
+    let hasher = if uses_hash {
+        quote! {
+            struct __DebugRedactHasher(u64);
+            impl core::hash::Hasher for __DebugRedactHasher {
+                fn finish(&self) -> u64 {
+                    self.0
+                }
+                fn write(&mut self, bytes: &[u8]) {
+                    for byte in bytes {
+                        self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x0100_0000_01b3);
+                    }
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    // For the `bytes` and `duration` presets.
+    let uses_preset = |preset: fn(&Preset) -> bool| {
+        bodies.iter().any(|body| {
+            body.field_attrs
+                .iter()
+                .any(|attrs| attrs.preset.as_ref().is_some_and(preset))
+        })
+    };
+    let byte_size = if uses_preset(|preset| matches!(preset, Preset::Bytes)) {
+        quote! {
+            struct __DebugByteSize(u64);
+            impl core::fmt::Debug for __DebugByteSize {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+                    if self.0 < 1024 {
+                        return f.write_fmt(format_args!("{} B", self.0));
+                    }
+                    let mut size = self.0 as f64 / 1024.0;
+                    let mut unit = 0;
+                    while size >= 1024.0 && unit < UNITS.len() - 1 {
+                        size /= 1024.0;
+                        unit += 1;
+                    }
+                    f.write_fmt(format_args!("{:.1} {}", size, UNITS[unit]))
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+    let human_duration = if uses_preset(|preset| matches!(preset, Preset::Duration)) {
+        quote! {
+            struct __DebugHumanDuration(core::time::Duration);
+            impl core::fmt::Debug for __DebugHumanDuration {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    let secs = self.0.as_secs();
+                    let (hours, minutes) = (secs / 3600, secs / 60 % 60);
+                    if hours > 0 {
+                        f.write_fmt(format_args!("{}h {}m {}s", hours, minutes, secs % 60))
+                    } else if minutes > 0 {
+                        f.write_fmt(format_args!("{}m {}s", minutes, secs % 60))
+                    } else {
+                        core::fmt::Debug::fmt(&self.0, f)
+                    }
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    // Prints the entries of a `#[debug(sorted)]` map or set in key order.
+    // With the "alloc" feature the entries are collected into a Vec and
+    // sorted. Without an allocator, every entry is found by a pass looking for
+    // the smallest key after the previous one, which is quadratic in the
+    // number of entries.
+    let uses_sorted = bodies
+        .iter()
+        .any(|body| body.field_attrs.iter().any(|attrs| attrs.sorted));
+    let for_each_sorted = if cfg!(feature = "alloc") {
+        quote! {
+            let mut entries: ::derive_debug::__private::Vec<_> = self.0.into_iter().collect();
+            // the keys of a map or set are distinct, so an unstable sort,
+            // which core provides, gives the same order
+            entries.sort_unstable_by(|a, b| a.key().cmp(b.key()));
+            for entry in entries {
+                f(entry);
+            }
+        }
+    } else {
+        quote! {
+            let mut prev: core::option::Option<<&'a T as core::iter::IntoIterator>::Item> =
+                core::option::Option::None;
+            loop {
+                let mut next = core::option::Option::None;
+                for entry in self.0 {
+                    let after_prev = match prev {
+                        core::option::Option::Some(prev) => entry.key() > prev.key(),
+                        core::option::Option::None => true,
+                    };
+                    let before_next = match next {
+                        core::option::Option::Some(next) => entry.key() < __DebugSortedEntry::key(next),
+                        core::option::Option::None => true,
+                    };
+                    if after_prev && before_next {
+                        next = core::option::Option::Some(entry);
+                    }
+                }
+                match next {
+                    core::option::Option::Some(entry) => f(entry),
+                    core::option::Option::None => return,
+                }
+                prev = next;
+            }
+        }
+    };
+    let sorted = if uses_sorted {
+        quote! {
+            trait __DebugSortedEntry<'a>: core::marker::Copy {
+                type Key: ?Sized + core::cmp::Ord + core::fmt::Debug + 'a;
+                fn key(self) -> &'a Self::Key;
+                // `None` for the elements of a set
+                fn value(self) -> core::option::Option<&'a dyn core::fmt::Debug>;
+            }
+            impl<'a, K, V> __DebugSortedEntry<'a> for (&'a K, &'a V)
+            where
+                K: ?Sized + core::cmp::Ord + core::fmt::Debug,
+                V: core::fmt::Debug,
+            {
+                type Key = K;
+                fn key(self) -> &'a K {
+                    self.0
+                }
+                fn value(self) -> core::option::Option<&'a dyn core::fmt::Debug> {
+                    core::option::Option::Some(self.1)
+                }
+            }
+            impl<'a, K> __DebugSortedEntry<'a> for &'a K
+            where
+                K: ?Sized + core::cmp::Ord + core::fmt::Debug,
+            {
+                type Key = K;
+                fn key(self) -> &'a K {
+                    self
+                }
+                fn value(self) -> core::option::Option<&'a dyn core::fmt::Debug> {
+                    core::option::Option::None
+                }
+            }
+            struct __DebugSorted<'a, T: ?Sized>(&'a T);
+            impl<'a, T: ?Sized> __DebugSorted<'a, T>
+            where
+                &'a T: core::iter::IntoIterator,
+                <&'a T as core::iter::IntoIterator>::Item: __DebugSortedEntry<'a>,
+            {
+                fn for_each(&self, mut f: impl FnMut(<&'a T as core::iter::IntoIterator>::Item)) {
+                    #for_each_sorted
+                }
+            }
+            impl<'a, T: ?Sized> core::fmt::Debug for __DebugSorted<'a, T>
+            where
+                &'a T: core::iter::IntoIterator,
+                <&'a T as core::iter::IntoIterator>::Item: __DebugSortedEntry<'a>,
+            {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    let is_map = match self.0.into_iter().next() {
+                        core::option::Option::Some(entry) => entry.value().is_some(),
+                        core::option::Option::None => false,
+                    };
+                    if is_map {
+                        let mut map = f.debug_map();
+                        self.for_each(|entry| {
+                            if let core::option::Option::Some(value) = entry.value() {
+                                map.entry(&entry.key(), &value);
+                            }
+                        });
+                        map.finish()
+                    } else {
+                        let mut set = f.debug_set();
+                        self.for_each(|entry| {
+                            set.entry(&entry.key());
+                        });
+                        set.finish()
+                    }
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    quote! {
+        #debug_with
+        #truncate
+        #hasher
+        #byte_size
+        #human_duration
+        #sorted
+    }
+}
+
+// Generated, for a struct or struct-like variant `Field { name, bitmask }`:
+// Field {
+//     name: ref __self_0,
+//     bitmask: ref __self_1,
+// } => f.debug_struct("Field")
+//     .field("name", __self_0)
+//     .field("bitmask", &format_args!("0b{:08b}", __self_1))
+//     .finish(),
+//
+// tuple structs and variants use `debug_tuple` with `Field(ref __self_0, ..)`
+// instead, and unit ones print just their name. Skipped fields are bound to
+// `_` and left out. With `non_exhaustive`, `.finish_non_exhaustive()` replaces
+// `.finish()` and unit ones print as `Field { .. }`. When a field has a
+// `pretty_field` format, the arm becomes
+// `if f.alternate() { /* pretty formats */ } else { /* usual formats */ }`.
+//
+// A struct or variant with its own `#[debug = "..."]` is written the way
+// CustomDisplay writes it, with the field attributes still applied, adding the
+// bounds its placeholders need to `predicates`, and a transparent one forwards to the Debug impl of its field:
+// `UserId(ref __self_0) => core::fmt::Debug::fmt(__self_0, f),`.
+fn debug_arm(
+    body: &Body,
+    non_exhaustive: bool,
+    generics: &Generics,
+    predicates: &mut Vec<WherePredicate>,
+) -> Result<TokenStream2> {
+    let path = &body.path;
+    let name = &body.name;
+    if let Some(format) = &body.format {
+        let (pattern, args) = format_fields(
+            path,
+            body.fields,
+            Some(&body.field_attrs),
+            format,
+            generics,
+            predicates,
+        )?;
+        return Ok(quote! {
+            #pattern => f.write_fmt(#args),
+        });
+    }
+    match body.fields {
+        Fields::Unit if non_exhaustive => {
+            return Ok(quote! {
+                #path => f.debug_struct(#name).finish_non_exhaustive(),
+            })
+        }
+        Fields::Unit => {
+            return Ok(quote! {
+                #path => f.write_str(#name),
+            })
+        }
+        _ => {}
+    }
+    let (pattern, copies) = body.bind_fields();
+    if body.transparent {
+        let field = body.fields.iter().next().unwrap();
+        let value = debug_value(
+            &format_ident!("__self_0"),
+            &field.ty,
+            &body.field_attrs[0],
+            false,
+        );
+        return Ok(quote! {
+            #pattern => {
+                #copies
+                core::fmt::Debug::fmt(#value, f)
+            }
+        });
+    }
+
+    let has_pretty = body
+        .field_attrs
+        .iter()
+        .any(|attrs| !attrs.skip && attrs.pretty_format.is_some());
+    let arm = if has_pretty {
+        let pretty = debug_fields(body, non_exhaustive, true);
+        let usual = debug_fields(body, non_exhaustive, false);
+        quote! {
+            #pattern => {
+                #copies
+                if f.alternate() { #pretty } else { #usual }
+            }
+        }
+    } else {
+        let usual = debug_fields(body, non_exhaustive, false);
+        quote! {
+            #pattern => {
+                #copies
+                #usual
+            }
+        }
+    };
+    Ok(arm)
+}
+
+// The `debug_struct` or `debug_tuple` call chain printing the shown fields of
+// a struct or variant with fields.
+fn debug_fields(body: &Body, non_exhaustive: bool, pretty: bool) -> TokenStream2 {
+    let name = &body.name;
+    let finish = if non_exhaustive {
+        quote!(finish_non_exhaustive)
+    } else {
+        quote!(finish)
+    };
+    let shown = body
+        .fields
+        .iter()
+        .zip(body.field_attrs.iter())
+        .enumerate()
+        .filter(|(_, (_, attrs))| !attrs.skip);
+    let values = shown.clone().map(|(i, (f, attrs))| {
+        let binding = format_ident!("__self_{}", i);
+        debug_value(&binding, &f.ty, attrs, pretty)
+    });
+
+    match body.fields {
+        Fields::Named(_) => {
+            let field_names = shown.map(|(_, (f, attrs))| match &attrs.rename {
+                Some(rename) => rename.value(),
+                None => f.ident.as_ref().unwrap().to_string(),
+            });
+            quote! {
+                f.debug_struct(#name)
+                    #( .field(#field_names, #values) )*
+                    .#finish()
+            }
+        }
+        _ => quote! {
+            f.debug_tuple(#name)
+                #( .field(#values) )*
+                .#finish()
+        },
+    }
+}
+
+// The `&dyn Debug` printed for one field, `binding` being a reference to it.
+// `pretty` selects the `pretty_field` format over the usual one.
+fn debug_value(binding: &Ident, ty: &Type, attrs: &FieldAttribute, pretty: bool) -> TokenStream2 {
+    if let Some(redact) = &attrs.redact {
+        return match redact {
+            Redact::Placeholder => quote!(&format_args!("<redacted>")),
+            Redact::Len => quote!(&format_args!("<redacted len={}>", #binding.len())),
+            Redact::Hash => quote!(&format_args!("<redacted hash={:016x}>", {
+                let mut hasher = __DebugRedactHasher(0xcbf2_9ce4_8422_2325);
+                core::hash::Hash::hash(#binding, &mut hasher);
+                core::hash::Hasher::finish(&hasher)
+            })),
+        };
+    }
+    if let Some(with) = &attrs.with {
+        return quote!(&__DebugWith(#binding, #with));
+    }
+    if let Some(truncate) = attrs.truncate {
+        // `&T: IntoIterator` holds for `T = [u8]` but not for `T = &[u8]`
+        let (value, ty) = deref_field(binding, ty);
+        return if is_byte_buffer(ty) {
+            quote!(&__DebugTruncateHex(&*#value, #truncate))
+        } else {
+            quote!(&__DebugTruncate(&*#value, #truncate))
+        };
+    }
+    if attrs.sorted {
+        let (value, _) = deref_field(binding, ty);
+        return quote!(&__DebugSorted(&*#value));
+    }
+    if let Some(preset) = attrs.preset {
+        let (value, ty) = deref_field(binding, ty);
+        return match preset {
+            Preset::Hex => int_format(&value, ty, "0x", 'x', 4),
+            Preset::Bin => int_format(&value, ty, "0b", 'b', 1),
+            Preset::Bytes => quote!(&__DebugByteSize(*#value as u64)),
+            Preset::Duration => quote!(&__DebugHumanDuration(*#value)),
+        };
+    }
+    let format = match &attrs.pretty_format {
+        Some(pretty_format) if pretty => Some(pretty_format),
+        _ => attrs.format.as_ref(),
+    };
+    // The conversion takes its input by value, and the field is only borrowed.
+    let value = match &attrs.via {
+        Some(via) => quote! {
+            &core::convert::Into::<#via>::into(core::clone::Clone::clone(#binding))
+        },
+        None => quote!(#binding),
+    };
+    match format {
+        Some(format) => quote!(&format_args!(#format, #value)),
+        None => value,
+    }
+}
+
+// `[u8]`, `[u8; N]`, or a collection of `u8` like `Vec<u8>`, going by the
+// type as written.
+fn is_byte_buffer(ty: &Type) -> bool {
+    match ty {
+        Type::Slice(slice) => is_u8(&slice.elem),
+        Type::Array(array) => is_u8(&array.elem),
+        Type::Path(path) => match &path.path.segments.last().unwrap().arguments {
+            PathArguments::AngleBracketed(args) => match args.args.first() {
+                Some(GenericArgument::Type(arg)) => args.args.len() == 1 && is_u8(arg),
+                _ => false,
+            },
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn is_u8(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path.qself.is_none() && path.path.is_ident("u8"),
+        _ => false,
+    }
+}
+
+// A reference to what the field points to, going through as many references
+// as its type as written has, along with the type it points to.
+fn deref_field<'a>(binding: &Ident, mut ty: &'a Type) -> (TokenStream2, &'a Type) {
+    let mut value = quote!(#binding);
+    while let Type::Reference(reference) = ty {
+        value = quote!(*#value);
+        ty = &reference.elem;
+    }
+    (value, ty)
+}
+
+// `&format_args!("0x{:04x}", value)` for `u16` and the `hex` preset: the value
+// zero-padded to the number of digits the integer type can need, each digit
+// standing for `digit_bits` bits. The width for `usize` and `isize` is taken
+// from the target, and types other than integers are not padded.
+fn int_format(
+    value: &TokenStream2,
+    ty: &Type,
+    prefix: &str,
+    spec: char,
+    digit_bits: usize,
+) -> TokenStream2 {
+    let ty_name = match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.get_ident().map(Ident::to_string),
+        _ => None,
+    };
+    let bits = match ty_name.as_deref() {
+        Some("u8") | Some("i8") => Some(8),
+        Some("u16") | Some("i16") => Some(16),
+        Some("u32") | Some("i32") => Some(32),
+        Some("u64") | Some("i64") => Some(64),
+        Some("u128") | Some("i128") => Some(128),
+        Some("usize") | Some("isize") => {
+            let format = format!("{}{{:01${}}}", prefix, spec);
+            return quote! {
+                &format_args!(#format, #value, core::mem::size_of::<#ty>() * 8 / #digit_bits)
+            };
+        }
+        _ => None,
+    };
+    let format = match bits {
+        Some(bits) => format!("{}{{:0{}{}}}", prefix, bits / digit_bits, spec),
+        None => format!("{}{{:{}}}", prefix, spec),
+    };
+    quote!(&format_args!(#format, #value))
+}
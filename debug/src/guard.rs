@@ -0,0 +1,48 @@
+// Runtime support for `#[debug(max_depth = N)]`. Every value of a type with
+// that option registers itself here while it is being printed, which is what
+// lets a nested value notice that it is too deep or that it is already being
+// printed further up the stack.
+
+use std::cell::RefCell;
+use std::thread_local;
+use std::vec::Vec;
+
+thread_local! {
+    // The values currently being printed on this thread, outermost first.
+    // Values are identified by address together with their type, since a
+    // struct and its first field can share an address.
+    static STACK: RefCell<Vec<(*const (), &'static str)>> = const { RefCell::new(Vec::new()) };
+}
+
+pub enum Enter {
+    // The value may be printed; it stays on the stack until the guard drops.
+    Entered(Guard),
+    // The value is already being printed by one of its callers.
+    Cycle,
+    // `max_depth` values are already being printed.
+    TooDeep,
+}
+
+pub struct Guard(());
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        STACK.with(|stack| {
+            stack.borrow_mut().pop();
+        });
+    }
+}
+
+pub fn enter(address: *const (), type_name: &'static str, max_depth: usize) -> Enter {
+    STACK.with(|stack| {
+        let mut stack = stack.borrow_mut();
+        if stack.contains(&(address, type_name)) {
+            Enter::Cycle
+        } else if stack.len() >= max_depth {
+            Enter::TooDeep
+        } else {
+            stack.push((address, type_name));
+            Enter::Entered(Guard(()))
+        }
+    })
+}
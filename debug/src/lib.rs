@@ -1,10 +1,62 @@
-extern crate proc_macro;
+// Crates that have the "proc-macro" crate type are only allowed to export
+// procedural macros. #[derive(Inspect)] implements a trait that user code has
+// to be able to name, so the derives live in the derive_debug_impl crate and
+// are re-exported from here together with that trait.
+//
+// Neither this crate nor the generated code uses anything outside of core, so
+// the derives work in no_std crates. The exceptions are
+// `#[debug(max_depth = N)]`, whose recursion guard keeps per-thread state and
+// needs the default "std" feature, and `#[debug(sorted)]`, which sorts in a
+// Vec with the "alloc" feature that "std" implies.
+#![no_std]
 
-use proc_macro::TokenStream;
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
-#[proc_macro_derive(CustomDebug)]
-pub fn derive(input: TokenStream) -> TokenStream {
-    let _ = input;
+#[cfg(feature = "std")]
+extern crate std;
 
-    unimplemented!()
+#[cfg(feature = "std")]
+mod guard;
+
+// Used by the generated code, not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "alloc")]
+    pub use alloc::vec::Vec;
+
+    #[cfg(feature = "std")]
+    pub use crate::guard::{enter, Enter};
+}
+
+pub use derive_debug_impl::{CustomDebug, CustomDisplay, Inspect};
+
+/// Implements `defmt::Format` from the same `#[debug(...)]` attributes as
+/// `CustomDebug`. The crate using it has to depend on defmt itself.
+#[cfg(feature = "defmt")]
+pub use derive_debug_impl::DefmtFormat;
+
+use core::fmt::Debug;
+
+/// Lists the fields of a value, for tooling that walks values without parsing
+/// their `{:?}` output.
+///
+/// `#[derive(Inspect)]` honors the same `#[debug(...)]` field attributes as
+/// `#[derive(CustomDebug)]`: skipped fields are not visited, renamed ones are
+/// visited under their new name and the value passed for a field prints the
+/// way `CustomDebug` would print it.
+pub trait Inspect {
+    fn inspect(&self, visitor: &mut dyn Visitor);
+}
+
+/// Receives the fields of a value passed to [`Inspect::inspect`].
+pub trait Visitor {
+    /// Called first for an enum, with the name of the variant being inspected.
+    fn visit_variant(&mut self, name: &str) {
+        let _ = name;
+    }
+
+    /// Called once per field, in declaration order. Tuple fields are named by
+    /// their index.
+    fn visit_field(&mut self, name: &str, type_name: &str, value: &dyn Debug);
 }
@@ -0,0 +1,66 @@
+// Besides structs with named fields, derive Debug for tuple structs, unit
+// structs and enums. Enum variants are printed the same way as the struct of
+// the corresponding shape would be: struct-like variants through
+// Formatter::debug_struct, tuple variants through Formatter::debug_tuple, and
+// unit variants as just their name.
+//
+// The #[debug = "..."] attribute is supported on tuple fields and on the fields
+// of variants too.
+//
+//
+// Resources:
+//
+//   - The DebugTuple helper for formatting tuple structs:
+//     https://doc.rust-lang.org/std/fmt/struct.DebugTuple.html
+
+use derive_debug::CustomDebug;
+use std::marker::PhantomData;
+
+#[derive(CustomDebug)]
+pub struct Register(&'static str, #[debug = "0x{:04x}"] u16);
+
+#[derive(CustomDebug)]
+pub struct Empty;
+
+#[derive(CustomDebug)]
+pub enum State<T> {
+    Idle,
+    Running(T, #[debug = "0b{:04b}"] u8),
+    Failed {
+        code: i32,
+        #[debug = "{}!"]
+        reason: &'static str,
+        marker: PhantomData<T>,
+    },
+}
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+fn main() {
+    assert_eq!(
+        format!("{:?}", Register("status", 0xbeef)),
+        r#"Register("status", 0xbeef)"#
+    );
+    assert_eq!(format!("{:?}", Empty), "Empty");
+
+    assert_eq!(format!("{:?}", State::<u8>::Idle), "Idle");
+    assert_eq!(
+        format!("{:?}", State::Running("job", 5)),
+        r#"Running("job", 0b0101)"#
+    );
+    assert_eq!(
+        format!(
+            "{:?}",
+            State::<()>::Failed {
+                code: -1,
+                reason: "timeout",
+                marker: PhantomData,
+            }
+        ),
+        "Failed { code: -1, reason: timeout!, marker: PhantomData<()> }"
+    );
+
+    fn assert_debug<T: std::fmt::Debug>() {}
+    assert_debug::<Never>();
+}
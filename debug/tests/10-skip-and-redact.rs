@@ -0,0 +1,70 @@
+// Fields holding secrets must never reach the logs. Support two field
+// attributes for them:
+//
+//   - #[debug(skip)] leaves the field out of the output entirely.
+//   - #[debug(redact)] prints the placeholder `<redacted>` in place of the
+//     value. With `redact = "len"` the placeholder also shows the value's
+//     len(), and with `redact = "hash"` a hash of the value computed through
+//     its Hash impl, so that two values can be told apart without revealing
+//     them.
+//
+// Neither kind of field is printed through its Debug impl, so they must not
+// cause Debug bounds to be inferred for their types. A type parameter of a
+// `redact = "hash"` field is bounded by Hash instead.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+#[derive(CustomDebug)]
+pub struct Credentials<K> {
+    user: &'static str,
+    #[debug(redact)]
+    password: String,
+    #[debug(redact = "len")]
+    token: Vec<u8>,
+    #[debug(redact = "hash")]
+    session: u64,
+    #[debug(skip)]
+    key: K,
+}
+
+#[derive(CustomDebug)]
+pub struct Session<H> {
+    #[debug(redact = "hash")]
+    id: H,
+}
+
+#[derive(CustomDebug)]
+pub struct Secret(#[debug(skip)] u32, &'static str);
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+    assert_debug::<Credentials<NotDebug>>();
+
+    let credentials = Credentials {
+        user: "admin",
+        password: "hunter2".to_owned(),
+        token: vec![0; 12],
+        session: 42,
+        key: NotDebug,
+    };
+    let debug = format!("{:?}", credentials);
+
+    assert!(debug.starts_with(
+        r#"Credentials { user: "admin", password: <redacted>, token: <redacted len=12>, session: <redacted hash="#
+    ));
+    assert!(!debug.contains("hunter2"));
+    assert!(!debug.contains("key"));
+
+    assert_eq!(format!("{:?}", Secret(7, "visible")), r#"Secret("visible")"#);
+
+    #[derive(Hash)]
+    struct Id(u64);
+    assert_debug::<Session<Id>>();
+    let session = format!("{:?}", Session { id: Id(42) });
+    assert!(session.starts_with("Session { id: <redacted hash="));
+    assert_eq!(session, format!("{:?}", Session { id: Id(42) }));
+    assert_ne!(session, format!("{:?}", Session { id: Id(43) }));
+}
@@ -0,0 +1,53 @@
+// Format strings can't express everything, such as printing a byte buffer as a
+// hex dump. Accept #[debug(with = "path::to::function")] on a field, naming a
+// function
+//
+//     fn(&T, &mut fmt::Formatter) -> fmt::Result
+//
+// which is called to render the field in place of its Debug impl. Since the
+// field's own Debug impl is never used, such a field must not cause a Debug
+// bound to be inferred for its type.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Debug};
+
+mod render {
+    use std::fmt;
+
+    pub fn hex(bytes: &Vec<u8>, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in bytes {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+fn opaque<T>(_: &T, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("<opaque>")
+}
+
+#[derive(CustomDebug)]
+pub struct Packet<T> {
+    id: u16,
+    #[debug(with = "render::hex")]
+    payload: Vec<u8>,
+    #[debug(with = "opaque")]
+    context: T,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct NotDebug;
+    assert_debug::<Packet<NotDebug>>();
+
+    let packet = Packet {
+        id: 7,
+        payload: vec![0xde, 0xad, 0xbe, 0xef],
+        context: NotDebug,
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        "Packet { id: 7, payload: deadbeef, context: <opaque> }"
+    );
+}
@@ -0,0 +1,56 @@
+// Snapshot tests should not break whenever an internal identifier is renamed.
+// Accept #[debug(rename = "...")] on the struct, on enum variants and on
+// fields to pick the name shown in the output.
+//
+// Also accept a container attribute #[debug(non_exhaustive)] which marks the
+// output as incomplete the same way Formatter's finish_non_exhaustive does,
+// for types where some fields are deliberately not shown.
+//
+//
+// Resources:
+//
+//   - DebugStruct::finish_non_exhaustive:
+//     https://doc.rust-lang.org/std/fmt/struct.DebugStruct.html#method.finish_non_exhaustive
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(rename = "Connection", non_exhaustive)]
+pub struct ConnInner {
+    #[debug(rename = "address")]
+    addr: &'static str,
+    #[debug(skip)]
+    fd: i32,
+}
+
+#[derive(CustomDebug)]
+pub enum Event {
+    #[debug(rename = "Open")]
+    Opened(u16),
+    Closed {
+        #[debug(rename = "reason")]
+        why: &'static str,
+    },
+}
+
+#[derive(CustomDebug)]
+#[debug(non_exhaustive)]
+pub struct Opaque;
+
+fn main() {
+    let conn = ConnInner {
+        addr: "127.0.0.1",
+        fd: 3,
+    };
+    assert_eq!(
+        format!("{:?}", conn),
+        r#"Connection { address: "127.0.0.1", .. }"#
+    );
+
+    assert_eq!(format!("{:?}", Event::Opened(80)), "Open(80)");
+    assert_eq!(
+        format!("{:?}", Event::Closed { why: "eof" }),
+        r#"Closed { reason: "eof" }"#
+    );
+    assert_eq!(format!("{:?}", Opaque), "Opaque { .. }");
+}
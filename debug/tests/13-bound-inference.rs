@@ -0,0 +1,118 @@
+// Extend the bound inference so that only type parameters and associated types
+// of type parameters are ever bounded, wherever they are nested in a field
+// type:
+//
+//   - `Vec<T::Value>` and `HashMap<K, T::Assoc>` produce `T::Value: Debug`,
+//     `K: Debug` and `T::Assoc: Debug`, but no `T: Debug`.
+//   - `<T as Trait>::Value` is bounded the same way as `T::Value`.
+//   - Mutually recursive types keep bounding just their parameters, so they
+//     don't overflow the trait solver the way `#field_ty: Debug` bounds would.
+//
+// Additionally accept #[debug(bound = "...")] on a field. It replaces only the
+// bounds that would have been inferred from that field, keeping those inferred
+// from the other fields, and `bound = ""` makes the field contribute none.
+//
+// A field with a format string is bounded by the trait its placeholder formats
+// through rather than by Debug: `T: Binary` for #[debug = "0b{:08b}"], and
+// also `T: LowerHex` for a #[debug(pretty_field = "{:#x}")] on the same field.
+
+use derive_debug::CustomDebug;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+pub trait Trait {
+    type Value;
+    type Assoc;
+}
+
+#[derive(CustomDebug)]
+pub struct Table<T: Trait, K> {
+    values: Vec<T::Value>,
+    index: HashMap<K, T::Assoc>,
+    qualified: Option<<T as Trait>::Value>,
+}
+
+#[derive(CustomDebug)]
+pub struct Node<T> {
+    value: T,
+    edges: Vec<Rc<Edge<T>>>,
+}
+
+#[derive(CustomDebug)]
+pub struct Edge<T> {
+    target: Option<Box<Node<T>>>,
+}
+
+#[derive(CustomDebug)]
+pub struct Wrapper<T: Trait, U> {
+    #[debug(bound = "T::Value: Debug")]
+    field: Field<T>,
+    #[debug(bound = "")]
+    shared: Rc<u8>,
+    normal: U,
+}
+
+#[derive(CustomDebug)]
+pub struct Field<T: Trait> {
+    values: Vec<T::Value>,
+}
+
+#[derive(CustomDebug)]
+pub struct Bits<T, U> {
+    #[debug = "0b{:08b}"]
+    #[debug(pretty_field = "{:#x}")]
+    bits: T,
+    #[debug = "{}"]
+    label: U,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    // Does not implement Debug, but its associated types do.
+    struct Id;
+
+    impl Trait for Id {
+        type Value = u8;
+        type Assoc = String;
+    }
+
+    assert_debug::<Table<Id, &str>>();
+    assert_debug::<Node<u8>>();
+    assert_debug::<Edge<u8>>();
+    assert_debug::<Wrapper<Id, bool>>();
+
+    // Only Binary and LowerHex, and only Display.
+    struct Mask;
+    impl std::fmt::Binary for Mask {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("101")
+        }
+    }
+    impl std::fmt::LowerHex for Mask {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("5")
+        }
+    }
+    struct Label;
+    impl std::fmt::Display for Label {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("mask")
+        }
+    }
+    assert_debug::<Bits<Mask, Label>>();
+    assert_eq!(
+        format!("{:?}", Bits { bits: Mask, label: Label }),
+        "Bits { bits: 0b101, label: mask }",
+    );
+
+    let node = Node {
+        value: 1,
+        edges: vec![Rc::new(Edge { target: None })],
+    };
+    assert_eq!(
+        format!("{:?}", node),
+        "Node { value: 1, edges: [Edge { target: None }] }"
+    );
+}
@@ -0,0 +1,98 @@
+// A companion #[derive(CustomDisplay)] in the same crate implements
+// std::fmt::Display from a format string given in a #[display = "..."]
+// attribute. The format string refers to fields by name, or by position for
+// tuple structs, and may use any format spec. Fields the format string doesn't
+// refer to are left out, including tuple fields before the last one used:
+//
+//     #[derive(CustomDisplay)]
+//     #[display = "{name} ({bitmask:08b})"]
+//     pub struct Field {
+//         name: &'static str,
+//         bitmask: u8,
+//     }
+//
+// Enums take a #[display = "..."] on every variant instead, and unit variants
+// without one print their name.
+//
+// Type parameters are bounded by the trait each placeholder formats through,
+// only for the fields that are actually printed, and #[display(bound = "...")]
+// replaces the inferred bounds the same way as #[debug(bound = "...")].
+
+use derive_debug::CustomDisplay;
+use std::fmt::{Display, LowerHex};
+
+#[derive(CustomDisplay)]
+#[display = "{name} ({bitmask:08b})"]
+pub struct Field {
+    name: &'static str,
+    bitmask: u8,
+}
+
+#[derive(CustomDisplay)]
+#[display = "{0}:{1:>2$}"]
+pub struct Pair<T>(T, u16, usize);
+
+#[derive(CustomDisplay)]
+#[display = "{1}"]
+pub struct Second(u8, u8);
+
+#[derive(CustomDisplay)]
+#[display = "{0} {2}"]
+pub struct Ends(u8, u8, u8);
+
+#[derive(CustomDisplay)]
+#[display = "{} {:.*}"]
+pub struct Reading(&'static str, usize, f64);
+
+#[derive(CustomDisplay)]
+pub enum Error<A> {
+    #[display = "not found: {0}"]
+    NotFound(&'static str),
+    #[display = "invalid address {addr:#x} (expected {expected})"]
+    Invalid { addr: A, expected: &'static str },
+    Timeout,
+}
+
+#[derive(CustomDisplay)]
+#[display(bound = "T: Display")]
+#[display = "<{inner}>"]
+pub struct Wrapper<T> {
+    inner: T,
+}
+
+fn assert_display<T: Display>() {}
+
+fn main() {
+    let field = Field {
+        name: "F",
+        bitmask: 0b00011100,
+    };
+    assert_eq!(field.to_string(), "F (00011100)");
+
+    assert_eq!(Pair("port", 80, 5).to_string(), "port:   80");
+    assert_eq!(Second(1, 2).to_string(), "2");
+    assert_eq!(Ends(1, 2, 3).to_string(), "1 3");
+    assert_eq!(Reading("t", 2, 21.456).to_string(), "t 21.46");
+
+    assert_eq!(
+        Error::<u32>::NotFound("config.toml").to_string(),
+        "not found: config.toml"
+    );
+    let invalid = Error::Invalid {
+        addr: 0xdeadu32,
+        expected: "aligned",
+    };
+    assert_eq!(invalid.to_string(), "invalid address 0xdead (expected aligned)");
+    assert_eq!(Error::<u32>::Timeout.to_string(), "Timeout");
+
+    assert_eq!(Wrapper { inner: 1 }.to_string(), "<1>");
+
+    // Only LowerHex is required of the address type.
+    struct Hex;
+    impl LowerHex for Hex {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("h")
+        }
+    }
+    assert_display::<Error<Hex>>();
+}
@@ -0,0 +1,14 @@
+// The format string of #[debug = "..."] is checked by the macro. The field is
+// passed as its only argument, so a string without a placeholder for it is an
+// error pointing at the literal rather than somewhere in the expansion.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Field {
+    name: &'static str,
+    #[debug = "bitmask"]
+    bitmask: u8,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+// Only the field is passed to the format string, so a second placeholder has
+// nothing to refer to. Where the compiler allows, the error points at the
+// offending placeholder inside the literal.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Field {
+    name: &'static str,
+    #[debug = "0b{:08b} ({})"]
+    bitmask: u8,
+}
+
+fn main() {}
@@ -0,0 +1,24 @@
+// Unbalanced braces and format specs which format_args! would not accept are
+// reported at the literal as well.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Field {
+    #[debug = "{:?"]
+    name: &'static str,
+}
+
+#[derive(CustomDebug)]
+pub struct Bits {
+    #[debug = "0b{:08q}"]
+    bitmask: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct Other {
+    #[debug = "{}}"]
+    value: u8,
+}
+
+fn main() {}
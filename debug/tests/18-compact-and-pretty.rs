@@ -0,0 +1,56 @@
+// Under {:#?} every nested struct is spread over several lines, which makes
+// large values hard to read. Accept two attributes to control this:
+//
+//   - #[debug(compact)] on a struct or enum keeps it on a single line even in
+//     alternate mode, along with everything nested inside it;
+//
+//   - #[debug(pretty_field = "...")] on a field gives a format string used
+//     instead of the field's usual one in alternate mode.
+//
+// Formatting the value again through "{:?}" is one way to get a Formatter
+// without the alternate flag.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(compact)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(CustomDebug)]
+pub struct Shape {
+    name: &'static str,
+    origin: Point,
+    #[debug(pretty_field = "{:#010b}")]
+    mask: u8,
+    #[debug = "{:.1}"]
+    #[debug(pretty_field = "{:.3}")]
+    scale: f64,
+}
+
+fn main() {
+    let point = Point { x: 1, y: -2 };
+    assert_eq!(format!("{:#?}", point), "Point { x: 1, y: -2 }");
+
+    let shape = Shape {
+        name: "square",
+        origin: point,
+        mask: 5,
+        scale: 1.5,
+    };
+    assert_eq!(
+        format!("{:?}", shape),
+        r#"Shape { name: "square", origin: Point { x: 1, y: -2 }, mask: 5, scale: 1.5 }"#,
+    );
+    assert_eq!(
+        format!("{:#?}", shape),
+        r#"Shape {
+    name: "square",
+    origin: Point { x: 1, y: -2 },
+    mask: 0b00000101,
+    scale: 1.500,
+}"#,
+    );
+}
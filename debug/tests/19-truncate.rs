@@ -0,0 +1,50 @@
+// Structs holding large buffers produce enormous Debug output. Accept a field
+// attribute #[debug(truncate = N)] which prints only the first N elements of
+// the field followed by how many were left out:
+//
+//     [1, 2, 3, ... (7 more)]
+//
+// It works for any field type T where &T implements IntoIterator with Debug
+// items, and references to such types. Buffers of bytes (u8 slices, arrays
+// and collections such as Vec<u8>) print the first N bytes as hex instead:
+//
+//     0xdeadbeef ... (1020 more)
+//
+//
+// Resources:
+//
+//   - DebugList, which also takes care of {:#?}:
+//     https://doc.rust-lang.org/std/fmt/struct.DebugList.html
+
+use derive_debug::CustomDebug;
+use std::collections::BTreeSet;
+
+#[derive(CustomDebug)]
+pub struct Packet<'a, T> {
+    #[debug(truncate = 4)]
+    header: [u8; 6],
+    #[debug(truncate = 2)]
+    payload: &'a [u8],
+    #[debug(truncate = 3)]
+    values: Vec<T>,
+    #[debug(truncate = 5)]
+    ids: BTreeSet<u32>,
+}
+
+fn main() {
+    let payload = vec![0xde, 0xad, 0xbe, 0xef];
+    let packet = Packet {
+        header: [1, 2, 0xab, 4, 5, 6],
+        payload: &payload,
+        values: (1..=10).collect(),
+        ids: (1..=3).collect(),
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        "Packet { \
+         header: 0x0102ab04 ... (2 more), \
+         payload: 0xdead ... (2 more), \
+         values: [1, 2, 3, ... (7 more)], \
+         ids: [1, 2, 3] }",
+    );
+}
@@ -0,0 +1,104 @@
+// Tooling such as an admin page rendering values as tables should not have to
+// parse {:?} output. Provide #[derive(Inspect)], implementing the
+// derive_debug::Inspect trait which hands every field to a Visitor as its name,
+// its type name and a &dyn Debug value:
+//
+//     pub trait Inspect {
+//         fn inspect(&self, visitor: &mut dyn Visitor);
+//     }
+//
+//     pub trait Visitor {
+//         fn visit_variant(&mut self, name: &str) {}
+//         fn visit_field(&mut self, name: &str, type_name: &str, value: &dyn Debug);
+//     }
+//
+// The derive reads the same attributes as CustomDebug, so skipped fields are
+// not visited and formatted or redacted fields are passed the way CustomDebug
+// prints them.
+//
+// A proc-macro crate can only export macros, so the trait lives in a separate
+// library crate which re-exports the derives, like derive_builder does for its
+// error type.
+//
+//
+// Resources:
+//
+//   - std::any::type_name:
+//     https://doc.rust-lang.org/std/any/fn.type_name.html
+
+use derive_debug::{CustomDebug, Inspect, Visitor};
+use std::any::type_name;
+use std::fmt::Debug;
+
+#[derive(CustomDebug, Inspect)]
+pub struct Field<T> {
+    name: &'static str,
+    #[debug = "0b{:08b}"]
+    bitmask: u8,
+    #[debug(skip)]
+    cache: Vec<u8>,
+    #[debug(rename = "value")]
+    inner: T,
+}
+
+#[derive(Inspect)]
+pub enum Event {
+    Opened(u16),
+    Closed {
+        #[debug(redact)]
+        secret: String,
+    },
+}
+
+#[derive(Default)]
+struct Table {
+    rows: Vec<String>,
+}
+
+impl Visitor for Table {
+    fn visit_variant(&mut self, name: &str) {
+        self.rows.push(format!("variant {}", name));
+    }
+
+    fn visit_field(&mut self, name: &str, type_name: &str, value: &dyn Debug) {
+        self.rows.push(format!("{} | {} | {:?}", name, type_name, value));
+    }
+}
+
+fn table(value: &dyn derive_debug::Inspect) -> Vec<String> {
+    let mut table = Table::default();
+    value.inspect(&mut table);
+    table.rows
+}
+
+fn main() {
+    let field = Field {
+        name: "F",
+        bitmask: 0b00011100,
+        cache: Vec::new(),
+        inner: Some(1u32),
+    };
+    assert_eq!(
+        table(&field),
+        [
+            r#"name | &str | "F""#.to_owned(),
+            "bitmask | u8 | 0b00011100".to_owned(),
+            format!("value | {} | Some(1)", type_name::<Option<u32>>()),
+        ],
+    );
+
+    assert_eq!(
+        table(&Event::Opened(80)),
+        ["variant Opened", "0 | u16 | 80"],
+    );
+    let closed = Event::Closed {
+        secret: "hunter2".to_owned(),
+    };
+    assert_eq!(
+        table(&closed),
+        [
+            "variant Closed".to_owned(),
+            format!("secret | {} | <redacted>", type_name::<String>()),
+        ],
+    );
+}
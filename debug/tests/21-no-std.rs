@@ -0,0 +1,58 @@
+// Firmware crates are #![no_std], so the generated impls may only name items
+// from core: core::fmt instead of std::fmt, and no String or format!
+// intermediates for fields with a custom format. format_args! already produces
+// a value implementing Debug without allocating, and the hash used by
+// #[debug(redact = "hash")] has to be computed by a hasher that doesn't come
+// from std.
+//
+// This test declares #![no_std] and links std under another name only to
+// run main, so any std:: path in the expansion fails to resolve.
+
+#![no_std]
+
+extern crate std as realstd;
+
+use derive_debug::{CustomDebug, CustomDisplay, Inspect, Visitor};
+use realstd::{assert_eq, format};
+
+#[derive(CustomDebug, CustomDisplay, Inspect)]
+#[display = "{name}"]
+pub struct Register<T> {
+    name: &'static str,
+    #[debug = "0x{:04x}"]
+    value: u16,
+    #[debug(truncate = 2)]
+    history: [T; 3],
+    #[debug(redact = "hash")]
+    key: u32,
+    #[debug(with = "core::fmt::Debug::fmt")]
+    flags: (bool, bool),
+}
+
+struct Count(usize);
+
+impl Visitor for Count {
+    fn visit_field(&mut self, _: &str, _: &str, _: &dyn core::fmt::Debug) {
+        self.0 += 1;
+    }
+}
+
+fn main() {
+    let register = Register {
+        name: "CTRL",
+        value: 0x2a,
+        history: [1, 2, 3],
+        key: 7,
+        flags: (true, false),
+    };
+    let debug = format!("{:?}", register);
+    assert!(debug.starts_with(
+        r#"Register { name: "CTRL", value: 0x002a, history: [1, 2, ... (1 more)], key: <redacted hash="#
+    ));
+    assert!(debug.ends_with(", flags: (true, false) }"));
+    assert_eq!(format!("{}", register), "CTRL");
+
+    let mut count = Count(0);
+    derive_debug::Inspect::inspect(&register, &mut count);
+    assert_eq!(count.0, 5);
+}
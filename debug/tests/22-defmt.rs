@@ -0,0 +1,161 @@
+// With the "defmt" cargo feature, derive_debug also exports
+// #[derive(DefmtFormat)] which implements defmt::Format from the same
+// attributes as CustomDebug, for firmware that logs through defmt instead of
+// core::fmt. The output has the same layout as CustomDebug's. Fields printed
+// as they are go through their own defmt::Format impl, while fields with a
+// format string or another attribute changing how they print are rendered
+// with core::fmt through defmt::Debug2Format.
+//
+// The generated code refers to `defmt::...` paths only, so this test stands in
+// a small module for the real crate.
+//
+//
+// Resources:
+//
+//   - The defmt book, on implementing Format by hand:
+//     https://defmt.ferrous-systems.com/format
+
+use derive_debug::DefmtFormat;
+
+mod defmt {
+    use std::cell::RefCell;
+    use std::fmt::Debug;
+
+    pub trait Format {
+        fn format(&self, fmt: Formatter<'_>);
+    }
+
+    #[derive(Clone, Copy)]
+    pub struct Formatter<'a> {
+        pub out: &'a RefCell<String>,
+    }
+
+    pub struct Debug2Format<'a, T: Debug + ?Sized>(pub &'a T);
+
+    impl<'a, T: Debug + ?Sized> Format for Debug2Format<'a, T> {
+        fn format(&self, fmt: Formatter<'_>) {
+            fmt.out.borrow_mut().push_str(&format!("{:?}", self.0));
+        }
+    }
+
+    impl<T: Format + ?Sized> Format for &T {
+        fn format(&self, fmt: Formatter<'_>) {
+            T::format(self, fmt)
+        }
+    }
+
+    impl Format for str {
+        fn format(&self, fmt: Formatter<'_>) {
+            fmt.out.borrow_mut().push_str(self);
+        }
+    }
+
+    impl Format for u32 {
+        fn format(&self, fmt: Formatter<'_>) {
+            fmt.out.borrow_mut().push_str(&self.to_string());
+        }
+    }
+
+    pub fn write_args(fmt: Formatter<'_>, format: &str, args: &[&dyn Format]) {
+        let mut args = args.iter();
+        let mut rest = format;
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix("{{") {
+                fmt.out.borrow_mut().push('{');
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix("}}") {
+                fmt.out.borrow_mut().push('}');
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix("{}") {
+                args.next().unwrap().format(fmt);
+                rest = after;
+            } else {
+                let c = rest.chars().next().unwrap();
+                fmt.out.borrow_mut().push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    macro_rules! write {
+        ($fmt:expr, $format:literal $(, $arg:expr)* $(,)?) => {
+            $crate::defmt::write_args($fmt, $format, &[$( &$arg as &dyn $crate::defmt::Format ),*])
+        };
+    }
+    pub(crate) use write;
+
+    pub fn to_string(value: &dyn Format) -> String {
+        let out = RefCell::new(String::new());
+        value.format(Formatter { out: &out });
+        out.into_inner()
+    }
+}
+
+#[derive(DefmtFormat)]
+pub struct Field<T> {
+    name: &'static str,
+    #[debug = "0b{:08b}"]
+    bitmask: u8,
+    #[debug(rename = "value")]
+    inner: T,
+}
+
+#[derive(DefmtFormat)]
+#[debug(non_exhaustive)]
+pub enum Event {
+    Opened(u32),
+    Closed {
+        #[debug(redact)]
+        reason: &'static str,
+        #[debug(skip)]
+        code: u16,
+    },
+    Reset,
+}
+
+#[derive(DefmtFormat)]
+#[debug(transparent)]
+pub struct UserId(u32);
+
+#[derive(DefmtFormat)]
+#[debug = "{0}/{1}"]
+pub struct Ratio(u32, u32);
+
+#[derive(DefmtFormat)]
+#[debug = "{user}:{password:?}"]
+pub struct Login {
+    user: &'static str,
+    #[debug(redact)]
+    password: &'static str,
+}
+
+fn main() {
+    let field = Field {
+        name: "F",
+        bitmask: 0b00011100,
+        inner: 7u32,
+    };
+    assert_eq!(
+        defmt::to_string(&field),
+        "Field { name: F, bitmask: 0b00011100, value: 7 }",
+    );
+
+    assert_eq!(defmt::to_string(&Event::Opened(80)), "Opened(80, ..)");
+    let closed = Event::Closed {
+        reason: "eof",
+        code: 3,
+    };
+    assert_eq!(
+        defmt::to_string(&closed),
+        "Closed { reason: <redacted>, .. }",
+    );
+    assert_eq!(defmt::to_string(&Event::Reset), "Reset { .. }");
+
+    assert_eq!(defmt::to_string(&UserId(42)), "42");
+    assert_eq!(defmt::to_string(&Ratio(3, 4)), "3/4");
+    let login = Login {
+        user: "bob",
+        password: "hunter2",
+    };
+    assert_eq!(defmt::to_string(&login), "bob:<redacted>");
+}
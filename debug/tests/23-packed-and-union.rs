@@ -0,0 +1,67 @@
+// Fields of a #[repr(packed)] struct may be unaligned, and taking a reference
+// to one is an error, so the usual `ref` bindings can't be used for them.
+// Instead copy every shown field out of the struct into a local and print a
+// reference to the copy. This needs the fields to be Copy, which is the case
+// for the hardware register layouts packed structs are used for.
+//
+// Which field of a union is active can't be known from the value, so unions
+// take a container attribute #[debug(with = "...")] naming a function
+// fn(&Self, &mut fmt::Formatter) -> fmt::Result, and print as an opaque
+// `<union>` without one.
+//
+//
+// Resources:
+//
+//   - References to packed fields:
+//     https://doc.rust-lang.org/nomicon/other-reprs.html#reprpacked
+
+use derive_debug::CustomDebug;
+use std::fmt;
+
+#[derive(CustomDebug)]
+#[repr(C, packed)]
+pub struct Register {
+    tag: u8,
+    #[debug = "0x{:08x}"]
+    value: u32,
+    #[debug(skip)]
+    reserved: u16,
+}
+
+#[derive(CustomDebug)]
+#[repr(packed(2))]
+pub struct Pair(u8, u64);
+
+#[derive(CustomDebug)]
+#[debug(with = "fmt_word")]
+pub union Word {
+    bits: u32,
+    bytes: [u8; 4],
+}
+
+fn fmt_word(word: &Word, f: &mut fmt::Formatter) -> fmt::Result {
+    // Every bit pattern is a valid u32.
+    write!(f, "Word({:#x})", unsafe { word.bits })
+}
+
+#[derive(CustomDebug)]
+pub union Opaque {
+    int: i64,
+    float: f64,
+}
+
+fn main() {
+    let register = Register {
+        tag: 1,
+        value: 0xbeef,
+        reserved: 0,
+    };
+    assert_eq!(
+        format!("{:?}", register),
+        "Register { tag: 1, value: 0x0000beef }",
+    );
+    assert_eq!(format!("{:?}", Pair(2, 3)), "Pair(2, 3)");
+
+    assert_eq!(format!("{:?}", Word { bytes: [0; 4] }), "Word(0x0)");
+    assert_eq!(format!("{:?}", Opaque { int: 1 }), "<union>");
+}
@@ -0,0 +1,14 @@
+// Fields of a packed struct are copied out before being printed, so each one
+// that is shown has to be Copy. The error should point at the offending
+// field's type.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[repr(packed)]
+pub struct Frame {
+    id: u16,
+    payload: Vec<u8>,
+}
+
+fn main() {}
@@ -0,0 +1,89 @@
+// Newtypes such as `struct UserId(u64)` print as `UserId(42)`, where just `42`
+// or `user:42` would read better. Accept two more attributes on structs and on
+// enum variants:
+//
+//   - #[debug = "..."] replaces the output of the struct or variant with a
+//     format string referring to its fields by name, or by index for tuple
+//     fields, the same way as #[display = "..."] in CustomDisplay does. Type
+//     parameters are bounded by the trait each placeholder formats through.
+//     Field attributes still apply: a redacted field stays redacted, and any
+//     field printed through its #[debug(...)] options is formatted with `{:?}`.
+//
+//   - #[debug(transparent)] on a struct or variant with a single field prints
+//     only that field, through its own field attributes if it has any.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Display};
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct UserId(u64);
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct GroupId {
+    #[debug = "group:{}"]
+    id: u64,
+}
+
+#[derive(CustomDebug)]
+#[debug = "{0}/{1}"]
+pub struct Ratio(u32, u32);
+
+#[derive(CustomDebug)]
+#[debug = "v{1}"]
+pub struct Tagged(&'static str, u16);
+
+#[derive(CustomDebug)]
+#[debug = "{user}:{password:?} ({id:?})"]
+pub struct Login {
+    user: &'static str,
+    #[debug(redact)]
+    password: &'static str,
+    #[debug(hex)]
+    id: u16,
+}
+
+#[derive(CustomDebug)]
+pub enum Token<T> {
+    #[debug = "#{0}"]
+    Number(T),
+    #[debug(transparent)]
+    Word(&'static str),
+    #[debug = "<{start}..{end:02}>"]
+    Range { start: usize, end: usize },
+    #[debug = "eof"]
+    Eof,
+    Other(u8),
+}
+
+// Only Display, which the `#{0}` format needs.
+pub struct Digit(u8);
+
+impl Display for Digit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+fn main() {
+    assert_eq!(format!("{:?}", UserId(42)), "42");
+    assert_eq!(format!("{:?}", GroupId { id: 7 }), "group:7");
+    assert_eq!(format!("{:?}", Ratio(3, 4)), "3/4");
+    assert_eq!(format!("{:?}", Tagged("ignored", 2)), "v2");
+    let login = Login {
+        user: "bob",
+        password: "hunter2",
+        id: 42,
+    };
+    assert_eq!(format!("{:?}", login), "bob:<redacted> (0x002a)");
+
+    assert_eq!(format!("{:?}", Token::Number(Digit(5))), "#5");
+    assert_eq!(format!("{:?}", Token::<Digit>::Word("let")), r#""let""#);
+    assert_eq!(
+        format!("{:?}", Token::<Digit>::Range { start: 1, end: 3 }),
+        "<1..03>",
+    );
+    assert_eq!(format!("{:?}", Token::<Digit>::Eof), "eof");
+    assert_eq!(format!("{:?}", Token::<Digit>::Other(9)), "Other(9)");
+}
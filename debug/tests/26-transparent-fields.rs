@@ -0,0 +1,23 @@
+// #[debug(transparent)] needs exactly one field to forward to, and makes no
+// sense together with a #[debug = "..."] format for the same struct.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(transparent)]
+pub struct Point(i32, i32);
+
+#[derive(CustomDebug)]
+pub enum Shape {
+    #[debug(transparent)]
+    Empty,
+}
+
+#[derive(CustomDebug)]
+pub enum Figure {
+    #[debug(transparent)]
+    #[debug = "circle {0}"]
+    Circle(u32),
+}
+
+fn main() {}
@@ -0,0 +1,65 @@
+// Fields behind #[cfg(...)], such as telemetry only compiled in with some
+// feature, must only be printed when they exist, and
+// #[cfg_attr(predicate, debug = "...")] must apply the attribute exactly when
+// the predicate holds.
+//
+// The compiler evaluates #[cfg] and #[cfg_attr] on the item before handing it
+// to a derive macro, so the derive never sees fields or variants compiled
+// out, and sees enabled #[cfg_attr] attributes as plain attributes. Generated
+// code has to keep working from the fields it is given, which this test
+// checks with `all()`, which always holds, and `any()`, which never does.
+
+use derive_debug::{CustomDebug, Inspect, Visitor};
+use std::fmt::Debug;
+
+#[derive(CustomDebug, Inspect)]
+pub struct Connection {
+    id: u32,
+    #[cfg(any())]
+    bytes_sent: u64,
+    #[cfg(all())]
+    retries: u8,
+    #[cfg_attr(all(), debug = "0x{:04x}")]
+    flags: u16,
+    #[cfg_attr(any(), debug(skip))]
+    peer: &'static str,
+    #[cfg_attr(all(), debug(skip))]
+    secret: &'static str,
+}
+
+#[derive(CustomDebug)]
+pub enum State {
+    Idle,
+    #[cfg(any())]
+    Tracing(u64),
+    Busy(#[cfg_attr(all(), debug(redact))] &'static str),
+}
+
+struct Names(Vec<String>);
+
+impl Visitor for Names {
+    fn visit_field(&mut self, name: &str, _: &str, _: &dyn Debug) {
+        self.0.push(name.to_owned());
+    }
+}
+
+fn main() {
+    let connection = Connection {
+        id: 1,
+        retries: 2,
+        flags: 0x10,
+        peer: "10.0.0.1",
+        secret: "hunter2",
+    };
+    assert_eq!(
+        format!("{:?}", connection),
+        r#"Connection { id: 1, retries: 2, flags: 0x0010, peer: "10.0.0.1" }"#,
+    );
+
+    let mut names = Names(Vec::new());
+    derive_debug::Inspect::inspect(&connection, &mut names);
+    assert_eq!(names.0, ["id", "retries", "flags", "peer"]);
+
+    assert_eq!(format!("{:?}", State::Idle), "Idle");
+    assert_eq!(format!("{:?}", State::Busy("job")), "Busy(<redacted>)");
+}
@@ -0,0 +1,95 @@
+// Writing out format strings like "0b{:08b}" for every register field is
+// repetitive and easy to get inconsistent. Accept named presets as field
+// attributes:
+//
+//   - #[debug(hex)] prints `0x` and the value in hex, zero-padded to the
+//     number of digits the field's integer type can need, e.g. `0x002a` for a
+//     u16. Pointer-sized integers are padded for the target.
+//   - #[debug(bin)] does the same in binary with `0b`.
+//   - #[debug(bytes)] prints an integer byte count as a size, e.g. `1.5 KiB`.
+//   - #[debug(duration)] prints a Duration of a minute or more in hours,
+//     minutes and seconds, e.g. `1h 2m 3s`, and shorter ones the way
+//     Duration's Debug impl does.
+//
+// Type parameters of fields using hex or bin are bounded by LowerHex and
+// Binary rather than Debug.
+
+use derive_debug::CustomDebug;
+use std::time::Duration;
+
+#[derive(CustomDebug)]
+pub struct Registers<'a, T> {
+    #[debug(hex)]
+    status: u8,
+    #[debug(hex)]
+    control: u16,
+    #[debug(bin)]
+    mask: u8,
+    #[debug(hex)]
+    address: &'a u32,
+    #[debug(hex)]
+    data: T,
+}
+
+#[derive(CustomDebug)]
+pub struct Stats {
+    #[debug(bytes)]
+    small: u32,
+    #[debug(bytes)]
+    medium: u64,
+    #[debug(bytes)]
+    large: usize,
+    #[debug(duration)]
+    uptime: Duration,
+    #[debug(duration)]
+    latency: Duration,
+}
+
+// Only LowerHex, which the hex preset needs.
+pub struct Word(u32);
+
+impl std::fmt::LowerHex for Word {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+#[derive(CustomDebug)]
+pub struct Pointer {
+    #[debug(hex)]
+    addr: usize,
+}
+
+fn main() {
+    let registers = Registers {
+        status: 0x5,
+        control: 0x2a,
+        mask: 0b101,
+        address: &0xbeef,
+        data: Word(0x1f),
+    };
+    assert_eq!(
+        format!("{:?}", registers),
+        "Registers { status: 0x05, control: 0x002a, mask: 0b00000101, \
+         address: 0x0000beef, data: 0x1f }",
+    );
+
+    let stats = Stats {
+        small: 512,
+        medium: 1536,
+        large: 3 * 1024 * 1024 * 1024,
+        uptime: Duration::from_secs(3723),
+        latency: Duration::from_millis(15),
+    };
+    assert_eq!(
+        format!("{:?}", stats),
+        "Stats { small: 512 B, medium: 1.5 KiB, large: 3.0 GiB, \
+         uptime: 1h 2m 3s, latency: 15ms }",
+    );
+
+    let digits = std::mem::size_of::<usize>() * 2;
+    assert_eq!(
+        format!("{:?}", Pointer { addr: 0x10 }),
+        format!("Pointer {{ addr: 0x{:0width$x} }}", 0x10, width = digits),
+    );
+}
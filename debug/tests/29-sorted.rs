@@ -0,0 +1,40 @@
+// HashMap and HashSet iterate in an order that changes from run to run, which
+// makes snapshot tests of Debug output flaky. Accept #[debug(sorted)] on map
+// and set fields to print their entries in key order instead.
+//
+// Only the keys need to be Ord, and only for fields using the attribute. A
+// key type that is a type parameter gets the bound inferred.
+//
+// With the "alloc" feature of derive_debug, on by default through "std", the
+// entries are collected into a Vec and sorted. no_std crates without an
+// allocator can't do that (see 21-no-std.rs), so without the feature the field
+// is iterated once per entry instead, each time looking for the smallest key
+// after the last one printed. That needs no allocation but takes a quadratic
+// number of comparisons.
+
+use derive_debug::CustomDebug;
+use std::collections::{HashMap, HashSet};
+
+#[derive(CustomDebug)]
+pub struct Index<K> {
+    #[debug(sorted)]
+    counts: HashMap<K, u32>,
+    #[debug(sorted)]
+    tags: HashSet<&'static str>,
+    #[debug(sorted)]
+    empty: HashMap<u8, u8>,
+}
+
+fn main() {
+    let index = Index {
+        counts: (0..20).map(|i| (i * 7 % 20, i)).collect(),
+        tags: ["zeta", "alpha", "mu"].iter().copied().collect(),
+        empty: HashMap::new(),
+    };
+    assert_eq!(
+        format!("{:?}", index),
+        "Index { counts: {0: 0, 1: 3, 2: 6, 3: 9, 4: 12, 5: 15, 6: 18, 7: 1, \
+         8: 4, 9: 7, 10: 10, 11: 13, 12: 16, 13: 19, 14: 2, 15: 5, 16: 8, \
+         17: 11, 18: 14, 19: 17}, tags: {\"alpha\", \"mu\", \"zeta\"}, empty: {} }",
+    );
+}
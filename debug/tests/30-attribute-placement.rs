@@ -0,0 +1,32 @@
+// Some #[debug(...)] options only make sense on fields, some only on the
+// struct or enum, and a bare #[debug] means nothing at all. Each of these
+// should be rejected with an error that names where the option does belong,
+// pointing at the option's name rather than the whole attribute.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(skip)]
+pub struct Container {
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub enum Variant {
+    #[debug(bound = "T: Clone")]
+    Value(u8),
+}
+
+#[derive(CustomDebug)]
+pub struct Field {
+    #[debug(non_exhaustive)]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct Bare {
+    #[debug]
+    value: u8,
+}
+
+fn main() {}
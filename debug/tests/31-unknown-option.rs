@@ -0,0 +1,30 @@
+// A misspelled option should not be silently ignored. The error lists the
+// options accepted in that position and points at the unknown name.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(renam = "Other")]
+pub struct Container {
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub enum Variant {
+    #[debug(transparant)]
+    Value(u8),
+}
+
+#[derive(CustomDebug)]
+pub struct Field {
+    #[debug(skipp)]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct Literal {
+    #[debug("skip")]
+    value: u8,
+}
+
+fn main() {}
@@ -0,0 +1,38 @@
+// The value of `bound` is a string holding where-clause predicates. A value
+// that isn't a string should be reported at the value, and so should a string
+// that doesn't parse as predicates. The same goes for the other options that
+// take a value, such as `truncate`.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(bound = 1)]
+pub struct NotAString<T> {
+    value: T,
+}
+
+#[derive(CustomDebug)]
+#[debug(bound = "T: Debug for T")]
+pub struct BadPredicate<T> {
+    value: T,
+}
+
+#[derive(CustomDebug)]
+pub struct BadFieldPredicate<T> {
+    #[debug(bound = "T Debug")]
+    value: T,
+}
+
+#[derive(CustomDebug)]
+pub struct NotAnInteger {
+    #[debug(truncate = "8")]
+    value: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+pub struct NotAFormatString {
+    #[debug = 8]
+    value: u8,
+}
+
+fn main() {}
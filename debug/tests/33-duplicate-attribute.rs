@@ -0,0 +1,27 @@
+// Options can be spread across several #[debug] attributes on the same item,
+// but giving one twice is almost certainly a mistake, so the second occurrence
+// is reported even when both would agree.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(rename = "A")]
+#[debug(rename = "B")]
+pub struct Container {
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub enum Variant {
+    #[debug = "one"]
+    #[debug = "two"]
+    Value(u8),
+}
+
+#[derive(CustomDebug)]
+pub struct Field {
+    #[debug(skip, skip)]
+    value: u8,
+}
+
+fn main() {}
@@ -0,0 +1,105 @@
+// Graphs built from Rc<RefCell<...>> often point back at their own ancestors,
+// and the usual derived Debug follows those pointers until the stack
+// overflows. With #[debug(max_depth = N)] on a type, printing a value of that
+// type keeps track of the values of such types currently being printed on the
+// thread:
+//
+//     - a value reached again while it is still being printed prints as
+//       `<cycle>`;
+//
+//     - once N values with the option are nested in each other, the next one
+//       prints as `...`.
+//
+// The depth counts every type with the option, not only the type the option
+// was written on. The tracking lives in the derive_debug crate and needs its
+// "std" feature, which is on by default.
+
+use derive_debug::CustomDebug;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[derive(CustomDebug)]
+#[debug(max_depth = 8)]
+pub struct Node {
+    name: &'static str,
+    children: Vec<Rc<RefCell<Node>>>,
+    parent: Option<Rc<RefCell<Node>>>,
+}
+
+#[derive(CustomDebug)]
+#[debug(max_depth = 3)]
+pub struct List {
+    value: u8,
+    next: Option<Box<List>>,
+}
+
+#[derive(CustomDebug)]
+#[debug(max_depth = 2, compact)]
+pub struct Pair(u8, Option<Box<Pair>>);
+
+// The guard has to work where prelude names mean something else, as in
+// 41-redefined-prelude-types.rs.
+mod redefined {
+    use derive_debug::CustomDebug;
+
+    pub struct Option;
+    pub struct Some;
+    pub struct None;
+    pub struct Ok;
+    pub struct Result;
+    pub struct Box;
+
+    #[derive(CustomDebug)]
+    #[debug(max_depth = 2)]
+    pub struct Chain(pub u8, pub std::option::Option<std::boxed::Box<Chain>>);
+}
+
+fn main() {
+    let root = Rc::new(RefCell::new(Node {
+        name: "root",
+        children: Vec::new(),
+        parent: None,
+    }));
+    let child = Rc::new(RefCell::new(Node {
+        name: "child",
+        children: Vec::new(),
+        parent: Some(root.clone()),
+    }));
+    root.borrow_mut().children.push(child);
+    assert_eq!(
+        format!("{:?}", root.borrow()),
+        r#"Node { name: "root", children: [RefCell { value: Node { name: "child", children: [], parent: Some(RefCell { value: <cycle> }) } }], parent: None }"#,
+    );
+
+    let list = List {
+        value: 1,
+        next: Some(Box::new(List {
+            value: 2,
+            next: Some(Box::new(List {
+                value: 3,
+                next: Some(Box::new(List {
+                    value: 4,
+                    next: None,
+                })),
+            })),
+        })),
+    };
+    assert_eq!(
+        format!("{:?}", list),
+        "List { value: 1, next: Some(List { value: 2, next: Some(List { value: 3, next: Some(...) }) }) }",
+    );
+
+    // The guard is released after each value, so siblings don't count
+    // towards the depth and printing twice gives the same result.
+    assert_eq!(format!("{:?}", list), format!("{:?}", list));
+
+    let pair = Pair(1, Some(Box::new(Pair(2, Some(Box::new(Pair(3, None)))))));
+    assert_eq!(format!("{:#?}", pair), "Pair(1, Some(Pair(2, Some(...))))");
+
+    use redefined::Chain;
+    let chain = Chain(1, Some(Box::new(Chain(2, Some(Box::new(Chain(3, None)))))));
+    assert_eq!(format!("{:?}", chain), "Chain(1, Some(Chain(2, Some(...))))");
+
+    // Break the cycle so the nodes are freed.
+    root.borrow_mut().children.clear();
+}
@@ -0,0 +1,70 @@
+// Some fields are stored in a compact representation but are better read as
+// another type, like an IPv4 address kept as a u32 or a timestamp kept as
+// seconds. With #[debug(via = "Type")] a copy of the field is converted
+// through `Into<Type>` and the converted value is printed instead, through a
+// format string if the field has one.
+//
+// The field's own type doesn't have to implement Debug, so no such bound is
+// inferred for it. A generic field needs `Clone + Into<Type>` instead.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Debug};
+use std::net::Ipv4Addr;
+
+pub struct Timestamp(u64);
+
+impl From<u64> for Timestamp {
+    fn from(seconds: u64) -> Self {
+        Timestamp(seconds)
+    }
+}
+
+impl Debug for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "T+{}s", self.0)
+    }
+}
+
+#[derive(CustomDebug)]
+pub struct Connection {
+    #[debug(via = "Ipv4Addr")]
+    peer: u32,
+    #[debug(via = "Ipv4Addr")]
+    #[debug = "{}"]
+    gateway: u32,
+    #[debug(via = "Timestamp")]
+    opened: u64,
+}
+
+// Clone and Into<Ipv4Addr>, but not Debug.
+#[derive(Clone)]
+pub struct Raw(u32);
+
+impl From<Raw> for Ipv4Addr {
+    fn from(raw: Raw) -> Self {
+        Ipv4Addr::from(raw.0)
+    }
+}
+
+#[derive(CustomDebug)]
+pub struct Route<T> {
+    #[debug(via = "Ipv4Addr")]
+    next_hop: T,
+}
+
+fn main() {
+    let connection = Connection {
+        peer: 0x7f00_0001,
+        gateway: 0xc0a8_0001,
+        opened: 90,
+    };
+    assert_eq!(
+        format!("{:?}", connection),
+        "Connection { peer: 127.0.0.1, gateway: 192.168.0.1, opened: T+90s }",
+    );
+
+    let route = Route {
+        next_hop: Raw(0x0a00_0001),
+    };
+    assert_eq!(format!("{:?}", route), "Route { next_hop: 10.0.0.1 }");
+}
@@ -0,0 +1,12 @@
+// `via` replaces the value being printed, so it can't be combined with the
+// options that print the field some other way.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Packet {
+    #[debug(via = "std::net::Ipv4Addr", hex)]
+    source: u32,
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+// A format string on a struct or variant must not print what the field
+// attributes hide. Referring to a skipped field is an error, and so is
+// formatting a field that has #[debug(...)] options with anything but `{:?}`,
+// which is the only way those options apply.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug = "{user}:{token}"]
+pub struct Session {
+    user: &'static str,
+    #[debug(skip)]
+    token: u64,
+}
+
+#[derive(CustomDebug)]
+pub enum Credentials {
+    #[debug = "{0}:{1}"]
+    Basic(&'static str, #[debug(redact)] &'static str),
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+// Without the "std" feature of derive_debug there is nowhere to keep track of
+// the values being printed, so #[debug(max_depth = N)] is rejected with an
+// error naming the feature, instead of failing on the missing runtime support.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(max_depth = 4)]
+pub struct Node {
+    next: Option<Box<Node>>,
+}
+
+fn main() {}
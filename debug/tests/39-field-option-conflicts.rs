@@ -0,0 +1,45 @@
+// Options that decide what a field prints can't be used together, since all
+// but one of them would be silently ignored: at most one of `redact`, `with`,
+// `truncate`, `sorted`, `via` and the presets, no format string next to any of
+// them but `via`, and nothing at all next to `skip`. The error points at the
+// later of the two options.
+
+use derive_debug::CustomDebug;
+use std::fmt;
+
+fn shout(value: &u8, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}!", value)
+}
+
+#[derive(CustomDebug)]
+pub struct WithAndFormat {
+    #[debug(with = "shout")]
+    #[debug = "X{:?}X"]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct RedactAndTruncate {
+    #[debug(redact, truncate = 4)]
+    value: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+pub struct SkipAndRename {
+    #[debug(skip, rename = "other")]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct TwoPresets {
+    #[debug(hex, bin)]
+    value: u8,
+}
+
+#[derive(CustomDebug)]
+pub struct SortedAndPretty {
+    #[debug(pretty_field = "{:#?}", sorted)]
+    value: std::collections::HashSet<u8>,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+// An enum prints the name of its variant, never its own, so a
+// #[debug(rename = "...")] on the enum would have no effect. It is rejected
+// with a pointer to the variants, which accept it.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(rename = "Renamed")]
+pub enum State {
+    Idle,
+    Busy(u8),
+}
+
+fn main() {}
@@ -0,0 +1,117 @@
+// The same check as 09-redefined-prelude-types.rs in the builder crate: the
+// generated impls and the helper types emitted next to them have to refer to
+// everything through absolute paths, so that they still compile when the
+// caller's module gives prelude names a different meaning.
+//
+// Every field attribute that emits a helper is used here, along with the
+// struct-level options, so that none of them can fall back on the prelude.
+// `max_depth` needs the "std" feature and is checked in 34-max-depth.rs.
+
+use derive_debug::{CustomDebug, CustomDisplay, Inspect};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::net::Ipv4Addr;
+use std::time::Duration;
+
+pub struct Option;
+pub struct Some;
+pub struct None;
+pub struct Ok;
+pub struct Err;
+pub struct Result;
+pub struct Box;
+pub struct Vec;
+pub struct String;
+pub struct Copy;
+pub struct Clone;
+pub struct Into;
+pub struct From;
+pub struct Iterator;
+pub struct IntoIterator;
+pub struct Default;
+pub struct Drop;
+
+fn shout(value: &u8, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}!", value)
+}
+
+#[derive(CustomDebug, Inspect)]
+pub struct Everything {
+    #[debug(with = "shout")]
+    with: u8,
+    #[debug(truncate = 2)]
+    list: BTreeSet<u16>,
+    #[debug(truncate = 2)]
+    bytes: &'static [u8],
+    #[debug(redact = "hash")]
+    hashed: u64,
+    #[debug(redact = "len")]
+    secret: &'static str,
+    #[debug(bytes)]
+    size: u64,
+    #[debug(duration)]
+    elapsed: Duration,
+    #[debug(hex)]
+    hex: u16,
+    #[debug(sorted)]
+    map: HashMap<u8, u8>,
+    #[debug(sorted)]
+    set: HashSet<u8>,
+    #[debug(via = "Ipv4Addr")]
+    addr: u32,
+    #[debug = "{:.1}"]
+    #[debug(pretty_field = "{:.2}")]
+    ratio: f64,
+    #[debug(skip)]
+    skipped: (),
+}
+
+#[derive(CustomDebug)]
+#[debug(compact, non_exhaustive)]
+pub struct Compact(u8, #[debug(skip)] u8);
+
+#[derive(CustomDebug)]
+#[debug = "{1}"]
+pub struct Formatted(u8, u8);
+
+#[derive(CustomDebug, CustomDisplay)]
+#[display = "{0}"]
+#[debug(transparent)]
+pub struct Transparent(u8);
+
+#[derive(CustomDebug)]
+#[repr(packed)]
+pub struct Packed {
+    value: u32,
+}
+
+fn main() {
+    let everything = Everything {
+        with: 1,
+        list: [1, 2, 3].iter().copied().collect(),
+        bytes: &[0xab, 0xcd, 0xef],
+        hashed: 0,
+        secret: "hunter2",
+        size: 1536,
+        elapsed: Duration::from_secs(61),
+        hex: 0x2a,
+        map: [(2, 20), (1, 10)].iter().copied().collect(),
+        set: [3, 1, 2].iter().copied().collect(),
+        addr: 0x7f00_0001,
+        ratio: 0.5,
+        skipped: (),
+    };
+    let debug = format!("{:?}", everything);
+    assert!(debug.starts_with(
+        "Everything { with: 1!, list: [1, 2, ... (1 more)], bytes: 0xabcd ... (1 more), hashed: <redacted hash="
+    ));
+    assert!(debug.ends_with(
+        "secret: <redacted len=7>, size: 1.5 KiB, elapsed: 1m 1s, hex: 0x002a, map: {1: 10, 2: 20}, set: {1, 2, 3}, addr: 127.0.0.1, ratio: 0.5 }"
+    ));
+
+    assert_eq!(format!("{:#?}", Compact(1, 2)), "Compact(1, ..)");
+    assert_eq!(format!("{:?}", Formatted(1, 2)), "2");
+    assert_eq!(format!("{:?}", Transparent(3)), "3");
+    assert_eq!(format!("{}", Transparent(3)), "3");
+    assert_eq!(format!("{:?}", Packed { value: 4 }), "Packed { value: 4 }");
+}
@@ -0,0 +1,88 @@
+// The helper types behind `with`, `truncate`, `redact = "hash"`, `sorted`,
+// `bytes` and `duration` are declared inside the generated function, where
+// the field types are resolved as well. A type of the caller's that happens to
+// share a name with one of them must still be the one a field refers to, both
+// for conversions through `via` and for the type names Inspect reports.
+
+use derive_debug::{CustomDebug, Inspect, Visitor};
+use std::collections::HashSet;
+use std::fmt::{self, Debug};
+use std::time::Duration;
+
+macro_rules! user_type {
+    ($($name:ident)*) => {
+        $(
+            pub struct $name(u64);
+
+            impl From<u64> for $name {
+                fn from(value: u64) -> Self {
+                    $name(value)
+                }
+            }
+
+            impl Debug for $name {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    write!(f, "{}({})", stringify!($name), self.0)
+                }
+            }
+        )*
+    };
+}
+
+user_type!(DebugWith Truncate TruncateHex RedactHasher ByteSize HumanDuration Sorted SortedEntry);
+
+fn shout(value: &u8, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}!", value)
+}
+
+#[derive(CustomDebug, Inspect)]
+pub struct Probe {
+    #[debug(with = "shout")]
+    with: u8,
+    #[debug(truncate = 1)]
+    list: Vec<u16>,
+    #[debug(truncate = 1)]
+    bytes: Vec<u8>,
+    #[debug(redact = "hash")]
+    hashed: u64,
+    #[debug(bytes)]
+    size: u64,
+    #[debug(duration)]
+    elapsed: Duration,
+    #[debug(sorted)]
+    set: HashSet<u8>,
+    #[debug(via = "ByteSize")]
+    converted: u64,
+    user: HumanDuration,
+}
+
+struct TypeNames(Vec<(String, String)>);
+
+impl Visitor for TypeNames {
+    fn visit_field(&mut self, name: &str, type_name: &str, _value: &dyn Debug) {
+        self.0.push((name.to_owned(), type_name.to_owned()));
+    }
+}
+
+fn main() {
+    let probe = Probe {
+        with: 1,
+        list: vec![1, 2],
+        bytes: vec![0xab, 0xcd],
+        hashed: 0,
+        size: 2048,
+        elapsed: Duration::from_secs(5),
+        set: [2, 1].iter().copied().collect(),
+        converted: 7,
+        user: HumanDuration(9),
+    };
+    let debug = format!("{:?}", probe);
+    assert!(debug.ends_with(
+        "size: 2.0 KiB, elapsed: 5s, set: {1, 2}, converted: ByteSize(7), user: HumanDuration(9) }"
+    ));
+
+    let mut names = TypeNames(Vec::new());
+    derive_debug::Inspect::inspect(&probe, &mut names);
+    let (_, user) = names.0.iter().find(|(name, _)| name == "user").unwrap();
+    assert_eq!(*user, std::any::type_name::<HumanDuration>());
+}
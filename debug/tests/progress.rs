@@ -1,12 +1,49 @@
 #[test]
 fn tests() {
     let t = trybuild::TestCases::new();
-    //t.pass("tests/01-parse.rs");
-    //t.pass("tests/02-impl-debug.rs");
-    //t.pass("tests/03-custom-format.rs");
-    //t.pass("tests/04-type-parameter.rs");
-    //t.pass("tests/05-phantom-data.rs");
-    //t.pass("tests/06-bound-trouble.rs");
-    //t.pass("tests/07-associated-type.rs");
-    //t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/01-parse.rs");
+    t.pass("tests/02-impl-debug.rs");
+    t.pass("tests/03-custom-format.rs");
+    t.pass("tests/04-type-parameter.rs");
+    t.pass("tests/05-phantom-data.rs");
+    t.pass("tests/06-bound-trouble.rs");
+    t.pass("tests/07-associated-type.rs");
+    t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/09-enums-and-tuples.rs");
+    t.pass("tests/10-skip-and-redact.rs");
+    t.pass("tests/11-debug-with.rs");
+    t.pass("tests/12-rename-and-non-exhaustive.rs");
+    t.pass("tests/13-bound-inference.rs");
+    t.pass("tests/14-custom-display.rs");
+    t.compile_fail("tests/15-format-no-placeholder.rs");
+    t.compile_fail("tests/16-format-extra-placeholder.rs");
+    t.compile_fail("tests/17-format-invalid-spec.rs");
+    t.pass("tests/18-compact-and-pretty.rs");
+    t.pass("tests/19-truncate.rs");
+    t.pass("tests/20-inspect.rs");
+    t.pass("tests/21-no-std.rs");
+    #[cfg(feature = "defmt")]
+    t.pass("tests/22-defmt.rs");
+    t.pass("tests/23-packed-and-union.rs");
+    t.compile_fail("tests/24-packed-not-copy.rs");
+    t.pass("tests/25-variant-format-and-transparent.rs");
+    t.compile_fail("tests/26-transparent-fields.rs");
+    t.pass("tests/27-cfg-fields.rs");
+    t.pass("tests/28-presets.rs");
+    t.pass("tests/29-sorted.rs");
+    t.compile_fail("tests/30-attribute-placement.rs");
+    t.compile_fail("tests/31-unknown-option.rs");
+    t.compile_fail("tests/32-invalid-bound.rs");
+    t.compile_fail("tests/33-duplicate-attribute.rs");
+    #[cfg(feature = "std")]
+    t.pass("tests/34-max-depth.rs");
+    t.pass("tests/35-via.rs");
+    t.compile_fail("tests/36-via-conflict.rs");
+    t.compile_fail("tests/37-format-hidden-fields.rs");
+    t.compile_fail("tests/39-field-option-conflicts.rs");
+    t.compile_fail("tests/40-enum-rename.rs");
+    t.pass("tests/41-redefined-prelude-types.rs");
+    t.pass("tests/42-helper-names.rs");
+    #[cfg(not(feature = "std"))]
+    t.compile_fail("tests/38-max-depth-without-std.rs");
 }